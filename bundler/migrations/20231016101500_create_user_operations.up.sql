@@ -0,0 +1,15 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS user_operations
+(
+    user_op_hash     VARCHAR(66) PRIMARY KEY,
+    sender           VARCHAR(42)                                        NOT NULL,
+    nonce            NUMERIC                                            NOT NULL,
+    user_operation   JSONB                                              NOT NULL,
+    transaction_id   VARCHAR,
+    transaction_hash VARCHAR(66),
+    status           VARCHAR(10)                                        NOT NULL,
+    created_at       TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at       TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS user_operations_status_idx ON user_operations (status);
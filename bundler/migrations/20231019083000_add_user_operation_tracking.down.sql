@@ -0,0 +1,4 @@
+-- Add down migration script here
+ALTER TABLE user_operations
+    DROP COLUMN IF EXISTS submitted_block,
+    DROP COLUMN IF EXISTS submitted_at;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+ALTER TABLE user_operations
+    ADD COLUMN IF NOT EXISTS submitted_block BIGINT,
+    ADD COLUMN IF NOT EXISTS submitted_at    TIMESTAMP WITH TIME ZONE;
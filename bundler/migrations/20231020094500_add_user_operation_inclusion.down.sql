@@ -0,0 +1,5 @@
+-- Add down migration script here
+ALTER TABLE user_operations
+    DROP COLUMN IF EXISTS block_number,
+    DROP COLUMN IF EXISTS block_hash,
+    DROP COLUMN IF EXISTS success;
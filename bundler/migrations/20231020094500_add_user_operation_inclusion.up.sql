@@ -0,0 +1,5 @@
+-- Add up migration script here
+ALTER TABLE user_operations
+    ADD COLUMN IF NOT EXISTS block_number BIGINT,
+    ADD COLUMN IF NOT EXISTS block_hash   VARCHAR(66),
+    ADD COLUMN IF NOT EXISTS success      BOOLEAN;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+ALTER TABLE users
+    DROP COLUMN IF EXISTS owner_address,
+    DROP COLUMN IF EXISTS encrypted_owner_key;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS owner_address       VARCHAR(42),
+    ADD COLUMN IF NOT EXISTS encrypted_owner_key VARCHAR;
@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE users
+    DROP COLUMN IF EXISTS salt_scheme_version;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+-- existing rows were salted with the legacy DefaultHasher scheme
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS salt_scheme_version INTEGER DEFAULT 0 NOT NULL;
@@ -0,0 +1,8 @@
+-- Add down migration script here
+ALTER TABLE user_operations
+    DROP COLUMN IF EXISTS chain;
+
+DROP INDEX IF EXISTS users_email_chain_idx;
+
+ALTER TABLE users
+    DROP COLUMN IF EXISTS chain;
@@ -0,0 +1,9 @@
+-- Add up migration script here
+-- existing rows are assigned the current chain on startup
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS chain VARCHAR;
+
+CREATE UNIQUE INDEX IF NOT EXISTS users_email_chain_idx ON users (email, chain);
+
+ALTER TABLE user_operations
+    ADD COLUMN IF NOT EXISTS chain VARCHAR;
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS user_transactions_batch_id_idx;
+
+ALTER TABLE user_transactions
+    DROP COLUMN IF EXISTS batch_id;
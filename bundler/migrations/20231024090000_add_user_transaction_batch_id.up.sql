@@ -0,0 +1,5 @@
+-- Add up migration script here
+ALTER TABLE user_transactions
+    ADD COLUMN IF NOT EXISTS batch_id VARCHAR;
+
+CREATE INDEX IF NOT EXISTS user_transactions_batch_id_idx ON user_transactions (batch_id);
@@ -0,0 +1,10 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS call_targets
+(
+    chain      VARCHAR                                            NOT NULL,
+    address    VARCHAR(42)                                        NOT NULL,
+    name       VARCHAR                                            NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    UNIQUE (chain, address)
+);
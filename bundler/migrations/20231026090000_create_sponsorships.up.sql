@@ -0,0 +1,12 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS sponsorships
+(
+    user_op_hash VARCHAR(66) PRIMARY KEY,
+    chain        VARCHAR                                            NOT NULL,
+    sender       VARCHAR(42)                                        NOT NULL,
+    gas_limit    BIGINT                                             NOT NULL,
+    max_cost     NUMERIC                                            NOT NULL,
+    created_at   TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS sponsorships_chain_created_at_idx ON sponsorships (chain, created_at);
@@ -0,0 +1,12 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS paymaster_top_ups
+(
+    id               SERIAL PRIMARY KEY,
+    chain            VARCHAR                                            NOT NULL,
+    paymaster        VARCHAR(42)                                        NOT NULL,
+    amount_gwei      BIGINT                                             NOT NULL,
+    transaction_hash VARCHAR(66)                                        NOT NULL,
+    created_at       TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS paymaster_top_ups_chain_created_at_idx ON paymaster_top_ups (chain, created_at);
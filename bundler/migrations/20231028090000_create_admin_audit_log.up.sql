@@ -0,0 +1,15 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS admin_audit_log
+(
+    id               SERIAL PRIMARY KEY,
+    actor            VARCHAR                                            NOT NULL,
+    action           VARCHAR                                            NOT NULL,
+    parameters       JSONB                                              NOT NULL,
+    result           VARCHAR                                            NOT NULL,
+    error            TEXT,
+    transaction_hash VARCHAR(66),
+    created_at       TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS admin_audit_log_actor_idx ON admin_audit_log (actor);
+CREATE INDEX IF NOT EXISTS admin_audit_log_action_idx ON admin_audit_log (action);
@@ -0,0 +1,8 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS rate_limit_counters
+(
+    key          VARCHAR PRIMARY KEY,
+    window_start BIGINT                                             NOT NULL,
+    total        NUMERIC(78, 0)                                     NOT NULL,
+    updated_at   TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
@@ -0,0 +1,12 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS idempotency_keys
+(
+    id              SERIAL PRIMARY KEY,
+    user_id         VARCHAR                                            NOT NULL,
+    idempotency_key VARCHAR(255)                                       NOT NULL,
+    request_hash    VARCHAR(66)                                        NOT NULL,
+    transaction_id  VARCHAR,
+    created_at      TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at      TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    UNIQUE (user_id, idempotency_key)
+);
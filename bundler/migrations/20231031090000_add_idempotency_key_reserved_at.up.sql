@@ -0,0 +1,3 @@
+-- Add up migration script here
+ALTER TABLE idempotency_keys
+    ADD COLUMN IF NOT EXISTS reserved_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL;
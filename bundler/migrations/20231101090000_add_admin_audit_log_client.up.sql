@@ -0,0 +1,3 @@
+-- Add up migration script here
+ALTER TABLE admin_audit_log
+    ADD COLUMN IF NOT EXISTS client VARCHAR;
@@ -1,33 +1,267 @@
-use ethers::middleware::SignerMiddleware;
-use ethers::providers::{Http, Provider};
-use ethers::types::Address;
-use ethers_signers::LocalWallet;
+use actix_web::rt::time::timeout;
+use ethers::providers::Middleware;
+use ethers::types::{Address, H256};
+use log::{error, info, warn};
+use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
+use crate::bundler::mempool::{Mempool, MempoolEntry};
+use crate::bundler::receipt_tracker::ReceiptTracker;
+use crate::constants::Constants;
 use crate::contracts::entrypoint_provider::EntryPointProvider;
+use crate::db::dao::transaction_dao::TransactionDao;
 use crate::models::contract_interaction;
+use crate::models::contract_interaction::user_operation::UserOperation;
+use crate::models::contract_interaction::user_operation_error::UserOperationError;
+use crate::models::transfer::status::Status;
 use crate::provider::web3_provider::Web3Provider;
-use crate::CONFIG;
+use crate::signer::middleware_signer::RelayerClient;
+use crate::{CONFIG, PROVIDERS};
 
 #[derive(Clone)]
 pub struct Bundler {
-    pub signer: SignerMiddleware<Arc<Provider<Http>>, LocalWallet>,
+    pub chain: String,
+    pub signer: Arc<RelayerClient>,
     pub entrypoint: EntryPointProvider,
+    pub mempool: Mempool,
+    pub transaction_dao: TransactionDao,
+    pub receipt_tracker: ReceiptTracker,
 }
 
 impl Bundler {
-    pub async fn submit(
+    pub async fn add(
         &self,
-        user_op: contract_interaction::user_operation::UserOperation,
+        user_op: UserOperation,
+        user_op_hash: H256,
+        transaction_id: Option<String>,
+    ) -> Result<(), UserOperationError> {
+        // An operation queued behind its sender's pending one is simulated on top of it. Behind
+        // the operation deploying its wallet there is no account to simulate yet, so it is held
+        // and only validated when bundled after the deployment.
+        let queued = user_op.nonce > 0
+            && self
+                .mempool
+                .contains(user_op.sender, user_op.nonce - 1)
+                .await;
+        if !queued || self.is_deployed(user_op.sender).await {
+            self.validate(&user_op, queued).await?;
+        }
+        self.mempool
+            .add(user_op, user_op_hash, transaction_id)
+            .await
+            .map_err(|err| UserOperationError::new(UserOperationError::REJECTED_BY_MEMPOOL, err))
+    }
+
+    pub async fn validate(
+        &self,
+        user_op: &UserOperation,
+        queued: bool,
+    ) -> Result<(), UserOperationError> {
+        let result = if queued {
+            self.entrypoint
+                .simulate_validation_queued(user_op.clone())
+                .await?
+        } else {
+            self.entrypoint.simulate_validation(user_op.clone()).await?
+        };
+        let (_, _, sig_failed, valid_after, valid_until, _) = result.return_info;
+        if sig_failed {
+            return Err(UserOperationError::new(
+                UserOperationError::INVALID_SIGNATURE,
+                String::from("Invalid user operation signature or paymaster signature"),
+            ));
+        }
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        if valid_after > now {
+            return Err(UserOperationError::new(
+                UserOperationError::OUT_OF_TIME_RANGE,
+                String::from("User operation is not valid yet"),
+            ));
+        }
+        if valid_until != 0 && valid_until < now + Constants::MIN_VALIDITY_SECONDS {
+            return Err(UserOperationError::new(
+                UserOperationError::OUT_OF_TIME_RANGE,
+                String::from("User operation expires too soon"),
+            ));
+        }
+        Ok(())
+    }
+
+    pub async fn run(self) {
+        self.mempool.restore().await;
+        let interval = Duration::from_millis(CONFIG.mempool.bundle_interval_ms);
+        loop {
+            // either the interval elapses or the mempool fills up a bundle
+            let _ = timeout(interval, self.mempool.wait_for_bundle()).await;
+            while self.mempool.size().await > 0 {
+                let bundled = self.bundle(CONFIG.run_config.account_owner).await;
+                if bundled == 0 || self.mempool.size().await < CONFIG.mempool.max_bundle_size {
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn bundle(&self, beneficiary: Address) -> usize {
+        let mut entries = vec![];
+        let mut waiting = vec![];
+        let mut rejected = vec![];
+        // entries arrive in nonce order per sender, so a follow-up operation is simulated on
+        // top of its predecessor once that one made it into the bundle
+        let mut blocked: HashSet<Address> = HashSet::new();
+        for entry in self
+            .mempool
+            .take_bundle(CONFIG.mempool.max_bundle_size)
+            .await
+        {
+            let sender = entry.user_op.sender;
+            if blocked.contains(&sender)
+                || (entry.user_op.nonce > 0 && !self.is_deployed(sender).await)
+            {
+                blocked.insert(sender);
+                waiting.push(entry);
+                continue;
+            }
+            let queued = entries.last().is_some_and(|last: &MempoolEntry| {
+                last.user_op.sender == sender && last.user_op.nonce + 1 == entry.user_op.nonce
+            });
+            match self.validate(&entry.user_op, queued).await {
+                Ok(_) => entries.push(entry),
+                Err(err) => {
+                    blocked.insert(sender);
+                    if !queued && self.is_waiting_for_nonce(&entry, &err).await {
+                        waiting.push(entry);
+                    } else {
+                        warn!(
+                            "Dropping user operation {:?}: {}",
+                            entry.user_op_hash, err.reason
+                        );
+                        rejected.push(entry);
+                    }
+                }
+            }
+        }
+        self.mempool.requeue(waiting).await;
+        self.fail(rejected).await;
+        if entries.is_empty() {
+            return 0;
+        }
+        let user_op_hashes: Vec<String> = entries
+            .iter()
+            .map(|entry| format!("{:?}", entry.user_op_hash))
+            .collect();
+
+        let submitted_block = match PROVIDERS[&self.chain].get_block_number().await {
+            Ok(block) => block.as_u64(),
+            Err(err) => {
+                error!("Failed to get block number: {:?}", err);
+                self.mempool.requeue(entries).await;
+                return 0;
+            }
+        };
+        let result = self
+            .submit(
+                entries.iter().map(|entry| entry.user_op.clone()).collect(),
+                beneficiary,
+            )
+            .await;
+        match result {
+            Ok(txn_hash) => {
+                info!(
+                    "Bundle of {} user operations sent. Hash: {:?}",
+                    entries.len(),
+                    txn_hash
+                );
+                self.mempool
+                    .user_operation_dao
+                    .submit_user_operations(
+                        user_op_hashes,
+                        txn_hash.to_lowercase(),
+                        submitted_block,
+                    )
+                    .await;
+                self.receipt_tracker.notify();
+                entries.len()
+            }
+            Err(err) => {
+                error!(
+                    "Bundle of {} user operations failed: {}",
+                    entries.len(),
+                    err
+                );
+                self.fail(entries).await;
+                0
+            }
+        }
+    }
+
+    // a wallet that can't be looked up is left to the simulation to judge
+    async fn is_deployed(&self, sender: Address) -> bool {
+        match PROVIDERS[&self.chain].get_code(sender, None).await {
+            Ok(code) => !code.is_empty(),
+            Err(err) => {
+                warn!("Failed to get code of {:?}: {:?}", sender, err);
+                true
+            }
+        }
+    }
+
+    async fn is_waiting_for_nonce(&self, entry: &MempoolEntry, err: &UserOperationError) -> bool {
+        if !err.reason.starts_with(Constants::INVALID_NONCE_CODE) {
+            return false;
+        }
+        match self.entrypoint.get_nonce(entry.user_op.sender).await {
+            Ok(nonce) => nonce.low_u64() < entry.user_op.nonce,
+            Err(_) => true,
+        }
+    }
+
+    async fn fail(&self, entries: Vec<MempoolEntry>) {
+        if entries.is_empty() {
+            return;
+        }
+        self.mempool
+            .release(entries.iter().map(|entry| entry.user_op_hash).collect())
+            .await;
+        self.mempool
+            .user_operation_dao
+            .update_user_operations(
+                entries
+                    .iter()
+                    .map(|entry| format!("{:?}", entry.user_op_hash))
+                    .collect(),
+                None,
+                Status::FAILED.to_string(),
+            )
+            .await;
+        self.transaction_dao
+            .update_user_transactions_status(
+                entries
+                    .into_iter()
+                    .filter_map(|entry| entry.transaction_id)
+                    .collect(),
+                Status::FAILED.to_string(),
+            )
+            .await;
+    }
+
+    async fn submit(
+        &self,
+        user_ops: Vec<contract_interaction::user_operation::UserOperation>,
         beneficiary: Address,
     ) -> Result<String, String> {
-        let call_data = self.entrypoint.handle_ops(user_op, beneficiary).await;
+        let call_data = self.entrypoint.handle_ops(user_ops, beneficiary).await;
         if call_data.is_err() {
             return Err(String::from("failed to transfer"));
         }
         Web3Provider::execute(
             self.signer.clone(),
-            CONFIG.get_chain().entrypoint_address,
+            &self.chain,
+            CONFIG.chains[&self.chain].entrypoint_address,
             String::from("0"),
             call_data.unwrap(),
             self.entrypoint.abi(),
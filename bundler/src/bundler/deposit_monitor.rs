@@ -0,0 +1,243 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use actix_web::rt::time::sleep;
+use ethers::providers::Middleware;
+use ethers::types::{Address, H256, U256};
+use ethers::utils::format_ether;
+use log::{error, info, warn};
+
+use crate::constants::Constants;
+use crate::contracts::entrypoint_provider::EntryPointProvider;
+use crate::db::dao::paymaster_top_up_dao::PaymasterTopUpDao;
+use crate::models::admin::low_balance_alert::LowBalanceAlert;
+use crate::provider::paymaster_provider::PaymasterProvider;
+use crate::provider::web3_provider::Web3Provider;
+use crate::signer::middleware_signer::RelayerClient;
+use crate::{CONFIG, PROVIDERS};
+
+// Watches the paymaster's EntryPoint deposit and the relayer's balance. Either one running low
+// is reported to the webhook once, and the deposit can be refilled from the relayer.
+#[derive(Clone)]
+pub struct DepositMonitor {
+    pub chain: String,
+    pub entrypoint: EntryPointProvider,
+    pub paymaster: PaymasterProvider,
+    pub relayer_signer: Arc<RelayerClient>,
+    pub paymaster_top_up_dao: PaymasterTopUpDao,
+    client: reqwest::Client,
+}
+
+impl DepositMonitor {
+    pub fn new(
+        chain: String,
+        entrypoint: EntryPointProvider,
+        paymaster: PaymasterProvider,
+        relayer_signer: Arc<RelayerClient>,
+        paymaster_top_up_dao: PaymasterTopUpDao,
+    ) -> DepositMonitor {
+        DepositMonitor {
+            chain,
+            entrypoint,
+            paymaster,
+            relayer_signer,
+            paymaster_top_up_dao,
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_millis(
+                    CONFIG.deposit_monitor.webhook_timeout_ms,
+                ))
+                .build()
+                .unwrap(),
+        }
+    }
+
+    pub async fn run(self) {
+        let interval = Duration::from_secs(CONFIG.deposit_monitor.poll_interval_seconds);
+        let mut low = HashSet::new();
+        let mut pending_top_up = None;
+        loop {
+            self.check(&mut low, &mut pending_top_up).await;
+            sleep(interval).await;
+        }
+    }
+
+    // a low deposit is alerted on even while a top-up is on its way, which may never land
+    async fn check(
+        &self,
+        low: &mut HashSet<&'static str>,
+        pending_top_up: &mut Option<(H256, Instant)>,
+    ) {
+        let rules = &CONFIG.chains[&self.chain].deposits;
+        match self.paymaster.get_deposit().await {
+            Ok(deposit) => {
+                let threshold = Self::to_wei(rules.min_paymaster_deposit_gwei);
+                if deposit < threshold && rules.auto_top_up {
+                    self.top_up(pending_top_up).await;
+                }
+                self.track(
+                    low,
+                    Constants::PAYMASTER,
+                    CONFIG.chains[&self.chain].verifying_paymaster_address,
+                    deposit,
+                    threshold,
+                )
+                .await;
+            }
+            Err(err) => warn!("Paymaster deposit check failed on {}: {}", self.chain, err),
+        }
+
+        let relayer_address = self.relayer_signer.inner().address();
+        match PROVIDERS[&self.chain]
+            .get_balance(relayer_address, None)
+            .await
+        {
+            Ok(balance) => {
+                self.track(
+                    low,
+                    Constants::RELAYER,
+                    relayer_address,
+                    balance,
+                    Self::to_wei(rules.min_relayer_balance_gwei),
+                )
+                .await
+            }
+            Err(err) => warn!("Relayer balance check failed on {}: {:?}", self.chain, err),
+        }
+    }
+
+    // A top-up still waiting to be mined would otherwise be sent again on the next poll. One
+    // without a receipt past the receipt tracker's deadline is taken as dropped.
+    async fn top_up(&self, pending_top_up: &mut Option<(H256, Instant)>) {
+        if let Some((txn_hash, sent_at)) = *pending_top_up {
+            match PROVIDERS[&self.chain]
+                .get_transaction_receipt(txn_hash)
+                .await
+            {
+                Ok(Some(_)) => *pending_top_up = None,
+                Ok(None)
+                    if sent_at.elapsed().as_secs()
+                        >= CONFIG.receipt_tracker.deadline_seconds as u64 =>
+                {
+                    warn!(
+                        "Paymaster top-up {:?} on {} was not mined in time",
+                        txn_hash, self.chain
+                    );
+                    *pending_top_up = None;
+                }
+                _ => return,
+            }
+        }
+        let rules = &CONFIG.chains[&self.chain].deposits;
+        let topped_up = match self
+            .paymaster_top_up_dao
+            .get_top_up_total(self.chain.clone())
+            .await
+        {
+            Ok(topped_up) => topped_up as u64,
+            Err(_) => return,
+        };
+        let amount = rules
+            .top_up_gwei
+            .min(rules.max_daily_top_up_gwei.saturating_sub(topped_up));
+        if amount == 0 {
+            warn!("Daily paymaster top-up limit reached on {}", self.chain);
+            return;
+        }
+
+        let paymaster_address = CONFIG.chains[&self.chain].verifying_paymaster_address;
+        let data = self.entrypoint.add_deposit(paymaster_address).await;
+        if data.is_err() {
+            error!("Paymaster top-up failed on {}", self.chain);
+            return;
+        }
+        let result = Web3Provider::execute(
+            self.relayer_signer.clone(),
+            &self.chain,
+            CONFIG.chains[&self.chain].entrypoint_address,
+            Self::to_wei(amount).to_string(),
+            data.unwrap(),
+            self.entrypoint.abi(),
+        )
+        .await;
+        match result {
+            Ok(txn_hash) => {
+                info!(
+                    "Paymaster deposit on {} topped up by {} gwei. Hash: {}",
+                    self.chain, amount, txn_hash
+                );
+                self.paymaster_top_up_dao
+                    .create_top_up(
+                        self.chain.clone(),
+                        format!("{:?}", paymaster_address),
+                        amount,
+                        txn_hash.clone(),
+                    )
+                    .await;
+                *pending_top_up = txn_hash
+                    .parse()
+                    .ok()
+                    .map(|txn_hash| (txn_hash, Instant::now()));
+            }
+            Err(err) => error!("Paymaster top-up failed on {}: {}", self.chain, err),
+        }
+    }
+
+    async fn track(
+        &self,
+        low: &mut HashSet<&'static str>,
+        entity: &'static str,
+        address: Address,
+        balance: U256,
+        threshold: U256,
+    ) {
+        if balance >= threshold {
+            if low.remove(entity) {
+                info!("{} balance on {} recovered", entity, self.chain);
+            }
+            return;
+        }
+        if !low.insert(entity) {
+            return;
+        }
+        warn!(
+            "{} balance on {} is below {}: {}",
+            entity,
+            self.chain,
+            format_ether(threshold),
+            format_ether(balance)
+        );
+        self.alert(LowBalanceAlert {
+            chain: self.chain.clone(),
+            entity: entity.to_string(),
+            address,
+            balance: format_ether(balance),
+            threshold: format_ether(threshold),
+            currency: CONFIG.chains[&self.chain].currency.clone(),
+        })
+        .await;
+    }
+
+    async fn alert(&self, alert: LowBalanceAlert) {
+        let webhook_url = match &CONFIG.deposit_monitor.webhook_url {
+            Some(webhook_url) => webhook_url,
+            None => return,
+        };
+        let response = self
+            .client
+            .post(webhook_url)
+            .json(&alert)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        match response {
+            Ok(_) => {}
+            Err(err) if err.is_timeout() => error!("Low balance alert timed out: {:?}", err),
+            Err(err) => error!("Low balance alert failed: {:?}", err),
+        }
+    }
+
+    fn to_wei(gwei: u64) -> U256 {
+        U256::from(gwei) * U256::exp10(9)
+    }
+}
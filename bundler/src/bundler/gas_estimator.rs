@@ -0,0 +1,110 @@
+use ethers::abi::AbiEncode;
+use ethers::providers::Middleware;
+use ethers::types::{Bytes, TransactionRequest, U256};
+use log::warn;
+
+use crate::constants::Constants;
+use crate::contracts::entrypoint_provider::EntryPointProvider;
+use crate::models::contract_interaction::user_operation::UserOperation;
+use crate::models::contract_interaction::user_operation_error::UserOperationError;
+use crate::models::rpc::user_operation_gas_estimate::UserOperationGasEstimate;
+use crate::{CONFIG, PROVIDERS};
+
+#[derive(Clone)]
+pub struct GasEstimator {
+    pub chain: String,
+    pub entrypoint: EntryPointProvider,
+}
+
+impl GasEstimator {
+    pub async fn estimate(
+        &self,
+        user_op: &UserOperation,
+    ) -> Result<UserOperationGasEstimate, UserOperationError> {
+        let pre_verification_gas = self.get_pre_verification_gas(user_op);
+
+        // a one wei gas price keeps the prefund negligible and makes `paid` equal to the gas used
+        let mut simulation_op = user_op.clone();
+        simulation_op
+            .pre_verification_gas(pre_verification_gas)
+            .verification_gas_limit(CONFIG.gas_estimation.max_verification_gas)
+            .call_gas_limit(CONFIG.gas_estimation.max_call_gas);
+        simulation_op.max_fee_per_gas = 1;
+        simulation_op.max_priority_fee_per_gas = 1;
+        let execution_result = self.entrypoint.simulate_handle_op(simulation_op).await?;
+
+        let pre_op_gas = execution_result.pre_op_gas;
+        let verification_gas = pre_op_gas.saturating_sub(U256::from(pre_verification_gas));
+        let call_gas = if user_op.init_code.is_empty() {
+            self.estimate_call_gas(user_op).await?
+        } else {
+            execution_result.paid.saturating_sub(pre_op_gas)
+        };
+
+        Ok(UserOperationGasEstimate {
+            pre_verification_gas: U256::from(pre_verification_gas),
+            verification_gas_limit: Self::with_margin(
+                verification_gas,
+                CONFIG.gas_estimation.verification_gas_margin_percent,
+            ),
+            call_gas_limit: Self::with_margin(
+                call_gas,
+                CONFIG.gas_estimation.call_gas_margin_percent,
+            ),
+        })
+    }
+
+    pub async fn fill(&self, user_op: &mut UserOperation) -> Result<(), UserOperationError> {
+        let estimate = self.estimate(user_op).await?;
+        user_op
+            .pre_verification_gas(estimate.pre_verification_gas.low_u64())
+            .verification_gas_limit(estimate.verification_gas_limit.low_u64())
+            .call_gas_limit(estimate.call_gas_limit.low_u64());
+        Ok(())
+    }
+
+    pub fn get_pre_verification_gas(&self, user_op: &UserOperation) -> u64 {
+        let mut packed_op = user_op.clone();
+        if packed_op.signature.len() < Constants::DUMMY_SIGNATURE_SIZE {
+            packed_op.signature = Bytes::from(vec![1u8; Constants::DUMMY_SIGNATURE_SIZE]);
+        }
+        let packed = self
+            .entrypoint
+            .get_entry_point_user_operation_payload(packed_op)
+            .encode();
+        let call_data_cost: u64 = packed
+            .iter()
+            .map(|byte| match byte {
+                0 => Constants::ZERO_BYTE_GAS,
+                _ => Constants::NON_ZERO_BYTE_GAS,
+            })
+            .sum();
+        // abi encoding is always word aligned
+        let words = packed.len() as u64 / 32;
+        call_data_cost
+            + Constants::FIXED_BUNDLE_GAS
+            + Constants::PER_USER_OP_GAS
+            + Constants::PER_USER_OP_WORD_GAS * words
+    }
+
+    async fn estimate_call_gas(&self, user_op: &UserOperation) -> Result<U256, UserOperationError> {
+        let txn = TransactionRequest::new()
+            .from(CONFIG.chains[&self.chain].entrypoint_address)
+            .to(user_op.sender)
+            .data(user_op.calldata.clone());
+        PROVIDERS[&self.chain]
+            .estimate_gas(&txn.into(), None)
+            .await
+            .map_err(|err| {
+                warn!("Call gas estimation failed: {:?}", err);
+                UserOperationError::new(
+                    UserOperationError::REJECTED_BY_ENTRYPOINT,
+                    String::from("call data execution reverted"),
+                )
+            })
+    }
+
+    fn with_margin(gas: U256, margin_percent: u64) -> U256 {
+        gas * (100 + margin_percent) / 100
+    }
+}
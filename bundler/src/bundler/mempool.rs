@@ -0,0 +1,395 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use ethers::types::{Address, H256};
+use log::{error, info};
+use tokio::sync::{Mutex, Notify};
+
+use crate::db::dao::user_operation_dao::UserOperationDao;
+use crate::models::contract_interaction::user_operation::UserOperation;
+use crate::models::transfer::status::Status;
+use crate::CONFIG;
+
+#[derive(Clone)]
+pub struct MempoolEntry {
+    pub user_op: UserOperation,
+    pub user_op_hash: H256,
+    pub transaction_id: Option<String>,
+    sequence: u64,
+}
+
+// Operations taken into a bundle stay in `in_flight` until the receipt tracker settles them, so
+// their nonces are not handed out again while the bundle is on its way.
+#[derive(Default)]
+struct MempoolState {
+    senders: HashMap<Address, BTreeMap<u64, MempoolEntry>>,
+    in_flight: HashMap<H256, (Address, u64)>,
+    sequence: u64,
+    size: usize,
+}
+
+impl MempoolState {
+    fn insert(
+        &mut self,
+        user_op: UserOperation,
+        user_op_hash: H256,
+        transaction_id: Option<String>,
+    ) -> (usize, Option<MempoolEntry>) {
+        self.in_flight.remove(&user_op_hash);
+        self.sequence += 1;
+        let entry = MempoolEntry {
+            sequence: self.sequence,
+            user_op_hash,
+            transaction_id,
+            user_op: user_op.clone(),
+        };
+        let replaced = self
+            .senders
+            .entry(user_op.sender)
+            .or_default()
+            .insert(user_op.nonce, entry);
+        if replaced.is_none() {
+            self.size += 1;
+        }
+        (self.size, replaced)
+    }
+
+    // Senders are served by the arrival of their oldest pending operation, each with as many
+    // consecutive nonces as fit, lowest first, so a bundle runs them in nonce order.
+    fn take_bundle(&mut self, max_size: usize) -> Vec<MempoolEntry> {
+        let mut senders: Vec<(u64, Address)> = self
+            .senders
+            .iter()
+            .filter_map(|(sender, entries)| {
+                entries
+                    .values()
+                    .map(|entry| entry.sequence)
+                    .min()
+                    .map(|sequence| (sequence, *sender))
+            })
+            .collect();
+        senders.sort();
+
+        let mut bundle = vec![];
+        for (_, sender) in senders {
+            if bundle.len() >= max_size {
+                break;
+            }
+            let entries = self.senders.get_mut(&sender).unwrap();
+            let mut expected_nonce: Option<u64> = None;
+            while bundle.len() < max_size {
+                let nonce = match entries.keys().next() {
+                    Some(nonce) => *nonce,
+                    None => break,
+                };
+                if expected_nonce.is_some() && expected_nonce != Some(nonce) {
+                    break;
+                }
+                bundle.push(entries.remove(&nonce).unwrap());
+                expected_nonce = Some(nonce + 1);
+            }
+            if entries.is_empty() {
+                self.senders.remove(&sender);
+            }
+        }
+        self.size -= bundle.len();
+        for entry in &bundle {
+            self.in_flight.insert(
+                entry.user_op_hash,
+                (entry.user_op.sender, entry.user_op.nonce),
+            );
+        }
+        bundle
+    }
+
+    fn next_nonce(&self, sender: Address, on_chain_nonce: u64) -> u64 {
+        let pending = self
+            .senders
+            .get(&sender)
+            .and_then(|entries| entries.keys().next_back().copied());
+        let in_flight = self
+            .in_flight
+            .values()
+            .filter(|(in_flight_sender, _)| *in_flight_sender == sender)
+            .map(|(_, nonce)| *nonce)
+            .max();
+        match pending.max(in_flight) {
+            Some(nonce) if nonce >= on_chain_nonce => nonce + 1,
+            _ => on_chain_nonce,
+        }
+    }
+
+    fn check_admission(&self, user_op: &UserOperation) -> Result<(), String> {
+        let existing = self
+            .senders
+            .get(&user_op.sender)
+            .and_then(|entries| entries.get(&user_op.nonce));
+        if let Some(existing) = existing {
+            let bump = 100 + CONFIG.mempool.replacement_fee_bump_percent;
+            if (user_op.max_fee_per_gas as u128) * 100
+                < (existing.user_op.max_fee_per_gas as u128) * bump as u128
+                || (user_op.max_priority_fee_per_gas as u128) * 100
+                    < (existing.user_op.max_priority_fee_per_gas as u128) * bump as u128
+            {
+                return Err(String::from("replacement user operation underpriced"));
+            }
+            return Ok(());
+        }
+        if self.size >= CONFIG.mempool.max_size {
+            return Err(String::from("mempool is full"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct Mempool {
+    pub chain: String,
+    pub user_operation_dao: UserOperationDao,
+    state: Arc<Mutex<MempoolState>>,
+    notify: Arc<Notify>,
+}
+
+impl Mempool {
+    pub fn new(chain: String, user_operation_dao: UserOperationDao) -> Mempool {
+        Mempool {
+            chain,
+            user_operation_dao,
+            state: Arc::new(Mutex::new(MempoolState::default())),
+            notify: Arc::new(Notify::new()),
+        }
+    }
+
+    pub async fn restore(&self) {
+        // bundles sent before a restart still hold their senders' nonces
+        let submitted = self
+            .user_operation_dao
+            .get_user_operations_by_status(Status::SUBMITTED.to_string(), self.chain.clone())
+            .await;
+        let mut state = self.state.lock().await;
+        for record in submitted {
+            let user_op = serde_json::from_value::<UserOperation>(record.user_operation);
+            let user_op_hash = record.user_op_hash.parse::<H256>();
+            if let (Ok(user_op), Ok(user_op_hash)) = (user_op, user_op_hash) {
+                state
+                    .in_flight
+                    .insert(user_op_hash, (user_op.sender, user_op.nonce));
+            }
+        }
+        let pending = self
+            .user_operation_dao
+            .get_user_operations_by_status(Status::PENDING.to_string(), self.chain.clone())
+            .await;
+        let mut restored = 0;
+        for record in pending {
+            let user_op = serde_json::from_value::<UserOperation>(record.user_operation);
+            let user_op_hash = record.user_op_hash.parse::<H256>();
+            match (user_op, user_op_hash) {
+                (Ok(user_op), Ok(user_op_hash)) => {
+                    state.insert(user_op, user_op_hash, record.transaction_id);
+                    restored += 1;
+                }
+                _ => error!("Failed to restore user operation: {}", record.user_op_hash),
+            }
+        }
+        info!(
+            "Restored {} user operations into the {} mempool",
+            restored, self.chain
+        );
+    }
+
+    pub async fn add(
+        &self,
+        user_op: UserOperation,
+        user_op_hash: H256,
+        transaction_id: Option<String>,
+    ) -> Result<(), String> {
+        // held across the insert, so no other operation is admitted in between
+        let mut state = self.state.lock().await;
+        state.check_admission(&user_op)?;
+        self.user_operation_dao
+            .create_user_operation(
+                format!("{:?}", user_op_hash),
+                &user_op,
+                transaction_id.clone(),
+                Status::PENDING.to_string(),
+                self.chain.clone(),
+            )
+            .await?;
+        let (size, replaced) = state.insert(user_op, user_op_hash, transaction_id.clone());
+        drop(state);
+        if let Some(replaced) = replaced {
+            self.user_operation_dao
+                .drop_replaced_user_operation(
+                    format!("{:?}", replaced.user_op_hash),
+                    transaction_id,
+                )
+                .await;
+        }
+        if size >= CONFIG.mempool.max_bundle_size {
+            self.notify.notify_one();
+        }
+        Ok(())
+    }
+
+    pub async fn next_nonce(&self, sender: Address, on_chain_nonce: u64) -> u64 {
+        self.state.lock().await.next_nonce(sender, on_chain_nonce)
+    }
+
+    pub async fn size(&self) -> usize {
+        self.state.lock().await.size
+    }
+
+    pub async fn wait_for_bundle(&self) {
+        self.notify.notified().await
+    }
+
+    // whether the operation is waiting in the mempool or in a bundle on its way
+    pub async fn contains(&self, sender: Address, nonce: u64) -> bool {
+        let state = self.state.lock().await;
+        state
+            .senders
+            .get(&sender)
+            .is_some_and(|entries| entries.contains_key(&nonce))
+            || state.in_flight.values().any(|op| *op == (sender, nonce))
+    }
+
+    pub async fn requeue(&self, entries: Vec<MempoolEntry>) {
+        let mut state = self.state.lock().await;
+        for entry in entries {
+            state.insert(entry.user_op, entry.user_op_hash, entry.transaction_id);
+        }
+    }
+
+    pub async fn take_bundle(&self, max_size: usize) -> Vec<MempoolEntry> {
+        self.state.lock().await.take_bundle(max_size)
+    }
+
+    // an operation that was included, dropped or failed no longer holds its nonce
+    pub async fn release(&self, user_op_hashes: Vec<H256>) {
+        let mut state = self.state.lock().await;
+        for user_op_hash in user_op_hashes {
+            state.in_flight.remove(&user_op_hash);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{Address, Bytes, H256};
+
+    use super::MempoolState;
+    use crate::models::contract_interaction::user_operation::UserOperation;
+
+    fn user_op(sender: u64, nonce: u64) -> UserOperation {
+        UserOperation {
+            sender: Address::from_low_u64_be(sender),
+            nonce,
+            init_code: Bytes::default(),
+            calldata: Bytes::default(),
+            call_gas_limit: 0,
+            verification_gas_limit: 0,
+            pre_verification_gas: 0,
+            max_fee_per_gas: 0,
+            max_priority_fee_per_gas: 0,
+            paymaster_and_data: Bytes::default(),
+            signature: Bytes::default(),
+        }
+    }
+
+    fn add(state: &mut MempoolState, sender: u64, nonce: u64) {
+        state.insert(
+            user_op(sender, nonce),
+            H256::from_low_u64_be(sender * 1000 + nonce),
+            None,
+        );
+    }
+
+    fn take(state: &mut MempoolState, max_size: usize) -> Vec<(u64, u64)> {
+        state
+            .take_bundle(max_size)
+            .into_iter()
+            .map(|entry| (entry.user_op.sender.to_low_u64_be(), entry.user_op.nonce))
+            .collect()
+    }
+
+    #[test]
+    fn takes_consecutive_nonces_in_order() {
+        let mut state = MempoolState::default();
+        add(&mut state, 1, 2);
+        add(&mut state, 1, 0);
+        add(&mut state, 1, 1);
+        assert_eq!(take(&mut state, 10), vec![(1, 0), (1, 1), (1, 2)]);
+        assert_eq!(state.size, 0);
+        assert!(state.senders.is_empty());
+    }
+
+    #[test]
+    fn stops_at_a_nonce_gap() {
+        let mut state = MempoolState::default();
+        add(&mut state, 1, 0);
+        add(&mut state, 1, 1);
+        add(&mut state, 1, 3);
+        assert_eq!(take(&mut state, 10), vec![(1, 0), (1, 1)]);
+        assert_eq!(state.size, 1);
+        assert_eq!(take(&mut state, 10), vec![(1, 3)]);
+    }
+
+    #[test]
+    fn groups_senders_by_oldest_arrival() {
+        let mut state = MempoolState::default();
+        add(&mut state, 2, 5);
+        add(&mut state, 1, 0);
+        add(&mut state, 2, 6);
+        add(&mut state, 1, 1);
+        add(&mut state, 3, 9);
+        assert_eq!(
+            take(&mut state, 10),
+            vec![(2, 5), (2, 6), (1, 0), (1, 1), (3, 9)]
+        );
+    }
+
+    #[test]
+    fn respects_max_size() {
+        let mut state = MempoolState::default();
+        add(&mut state, 1, 0);
+        add(&mut state, 1, 1);
+        add(&mut state, 2, 0);
+        assert_eq!(take(&mut state, 1), vec![(1, 0)]);
+        assert_eq!(state.size, 2);
+        assert_eq!(take(&mut state, 10), vec![(1, 1), (2, 0)]);
+    }
+
+    #[test]
+    fn counts_in_flight_nonces_until_released() {
+        let mut state = MempoolState::default();
+        add(&mut state, 1, 4);
+        add(&mut state, 1, 5);
+        assert_eq!(take(&mut state, 10), vec![(1, 4), (1, 5)]);
+        let sender = Address::from_low_u64_be(1);
+        assert_eq!(state.next_nonce(sender, 4), 6);
+        state.in_flight.clear();
+        assert_eq!(state.next_nonce(sender, 4), 4);
+    }
+
+    #[test]
+    fn requeue_clears_in_flight() {
+        let mut state = MempoolState::default();
+        add(&mut state, 1, 0);
+        let entry = state.take_bundle(10).pop().unwrap();
+        assert_eq!(state.in_flight.len(), 1);
+        state.insert(entry.user_op, entry.user_op_hash, entry.transaction_id);
+        assert!(state.in_flight.is_empty());
+        assert_eq!(state.size, 1);
+    }
+
+    #[test]
+    fn replacement_keeps_size() {
+        let mut state = MempoolState::default();
+        add(&mut state, 1, 0);
+        let (size, replaced) = state.insert(user_op(1, 0), H256::from_low_u64_be(7), None);
+        assert_eq!(size, 1);
+        assert_eq!(replaced.unwrap().user_op_hash, H256::from_low_u64_be(1000));
+        assert_eq!(take(&mut state, 10), vec![(1, 0)]);
+    }
+}
@@ -1 +1,5 @@
 pub mod bundler;
+pub mod deposit_monitor;
+pub mod gas_estimator;
+pub mod mempool;
+pub mod receipt_tracker;
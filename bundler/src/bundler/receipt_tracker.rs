@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::rt::time::timeout;
+use chrono::Utc;
+use ethers::contract::LogMeta;
+use ethers::providers::Middleware;
+use ethers::types::{Log, H256};
+use log::{info, warn};
+use tokio::sync::Notify;
+
+use crate::bundler::mempool::Mempool;
+use crate::contracts::entrypoint_provider::{EntryPointProvider, UserOperationEventFilter};
+use crate::contracts::erc20_provider::Erc20Provider;
+use crate::contracts::simple_account_provider::SimpleAccountProvider;
+use crate::contracts::token_paymaster_provider::TokenPaymasterProvider;
+use crate::db::dao::transaction_dao::{Gas, TransactionDao, TransactionReceiptMetadata};
+use crate::db::dao::user_operation_dao::{
+    IncludedUserOperation, SubmittedUserOperation, UserOperationDao,
+};
+use crate::db::dao::wallet_dao::WalletDao;
+use crate::models::transfer::status::Status;
+use crate::provider::helpers::decode_revert_reason;
+use crate::{CONFIG, PROVIDERS};
+
+#[derive(Clone)]
+pub struct ReceiptTracker {
+    pub chain: String,
+    pub entrypoint: EntryPointProvider,
+    pub user_operation_dao: UserOperationDao,
+    pub transaction_dao: TransactionDao,
+    pub simple_account_provider: SimpleAccountProvider,
+    pub erc20_provider: Erc20Provider,
+    pub token_paymaster_providers: HashMap<String, TokenPaymasterProvider>,
+    pub wallet_dao: WalletDao,
+    pub mempool: Mempool,
+    pub notify: Arc<Notify>,
+}
+
+impl ReceiptTracker {
+    pub fn notify(&self) {
+        self.notify.notify_one();
+    }
+
+    // hands the operation's nonce back to the mempool once it is settled on chain
+    async fn release(&self, user_op_hash: &str) {
+        if let Ok(user_op_hash) = user_op_hash.parse::<H256>() {
+            self.mempool.release(vec![user_op_hash]).await;
+        }
+    }
+
+    // Submitted operations are read back from the database on every poll, so anything in
+    // flight during a restart is picked up again on startup.
+    pub async fn run(self) {
+        let min_interval = Duration::from_millis(CONFIG.receipt_tracker.min_poll_interval_ms);
+        let max_interval = Duration::from_millis(CONFIG.receipt_tracker.max_poll_interval_ms);
+        let mut interval = min_interval;
+        loop {
+            let notified = timeout(interval, self.notify.notified()).await.is_ok();
+            let resolved = self.track_submitted().await + self.reconcile_included().await;
+            interval = if notified || resolved > 0 {
+                min_interval
+            } else {
+                (interval * 2).min(max_interval)
+            };
+        }
+    }
+
+    async fn track_submitted(&self) -> usize {
+        let submitted = self
+            .user_operation_dao
+            .get_submitted_user_operations(self.chain.clone())
+            .await;
+        let from_block = match submitted.iter().map(|op| op.submitted_block).min() {
+            None => return 0,
+            Some(block) => block as u64,
+        };
+        let user_op_hashes: Vec<H256> = submitted
+            .iter()
+            .filter_map(|op| op.user_op_hash.parse().ok())
+            .collect();
+        let events = match self
+            .entrypoint
+            .get_user_operation_events(user_op_hashes, from_block)
+            .await
+        {
+            Ok(events) => events,
+            Err(_) => return 0,
+        };
+
+        let mut resolved = 0;
+        for user_op in submitted {
+            let event = events.iter().find(|(event, _)| {
+                format!("{:?}", H256::from(event.user_op_hash)) == user_op.user_op_hash
+            });
+            let done = match event {
+                Some((event, meta)) => {
+                    self.include(&user_op, event, meta).await;
+                    true
+                }
+                None => self.expire(&user_op).await,
+            };
+            if done {
+                resolved += 1;
+            }
+        }
+        resolved
+    }
+
+    // Included operations are only final once their block is `confirmation_depth` deep; until
+    // then every poll checks that the block is still canonical.
+    async fn reconcile_included(&self) -> usize {
+        let included = self
+            .user_operation_dao
+            .get_included_user_operations(self.chain.clone())
+            .await;
+        if included.is_empty() {
+            return 0;
+        }
+        let latest_block = match PROVIDERS[&self.chain].get_block_number().await {
+            Ok(block) => block.as_u64(),
+            Err(err) => {
+                warn!("Failed to get block number: {:?}", err);
+                return 0;
+            }
+        };
+        let mut resolved = 0;
+        for user_op in included {
+            let block_number = user_op.block_number as u64;
+            let canonical = match PROVIDERS[&self.chain].get_block(block_number).await {
+                Ok(block) => block
+                    .and_then(|block| block.hash)
+                    .is_some_and(|hash| format!("{:?}", hash) == user_op.block_hash),
+                Err(err) => {
+                    warn!("Failed to get block {}: {:?}", block_number, err);
+                    continue;
+                }
+            };
+            if !canonical {
+                if self.reorg(&user_op).await {
+                    resolved += 1;
+                }
+                continue;
+            }
+            if latest_block >= block_number + CONFIG.chains[&self.chain].confirmation_depth {
+                self.confirm(&user_op).await;
+                resolved += 1;
+            }
+        }
+        resolved
+    }
+
+    // A confirmed operation ran its sender's validation on chain, so a wallet deployed
+    // through its initCode exists from here on, whether or not the call itself succeeded.
+    async fn confirm(&self, user_op: &IncludedUserOperation) {
+        self.user_operation_dao
+            .update_user_operations(
+                vec![user_op.user_op_hash.clone()],
+                Some(user_op.transaction_hash.clone()),
+                Status::CONFIRMED.to_string(),
+            )
+            .await;
+        self.release(&user_op.user_op_hash).await;
+        self.wallet_dao
+            .update_wallet_deployed(user_op.sender.clone(), self.chain.clone())
+            .await;
+        if let Some(transaction_id) = user_op.transaction_id.clone() {
+            let status = if user_op.success {
+                Status::SUCCESS
+            } else {
+                Status::FAILED
+            };
+            self.transaction_dao
+                .update_user_transactions_status(vec![transaction_id], status.to_string())
+                .await;
+        }
+    }
+
+    // A bundle whose block was reorged out is either back in the node's mempool, or already
+    // mined elsewhere, in which case it is tracked again; otherwise it is gone for good.
+    async fn reorg(&self, user_op: &IncludedUserOperation) -> bool {
+        let txn_hash = match user_op.transaction_hash.parse::<H256>() {
+            Ok(txn_hash) => txn_hash,
+            Err(_) => {
+                warn!("Invalid bundle hash: {}", user_op.transaction_hash);
+                return false;
+            }
+        };
+        let (status, transaction_status) =
+            match PROVIDERS[&self.chain].get_transaction(txn_hash).await {
+                Ok(Some(_)) => (Status::SUBMITTED, Status::PENDING),
+                Ok(None) => (Status::DROPPED, Status::DROPPED),
+                Err(err) => {
+                    warn!("Failed to get bundle: {:?}, err: {:?}", txn_hash, err);
+                    return false;
+                }
+            };
+        warn!(
+            "Block {} of user operation {} is no longer canonical, user operation is {}",
+            user_op.block_hash,
+            user_op.user_op_hash,
+            status.to_string()
+        );
+        self.user_operation_dao
+            .revert_user_operation_inclusion(user_op.user_op_hash.clone(), status.to_string())
+            .await;
+        if matches!(status, Status::DROPPED) {
+            self.release(&user_op.user_op_hash).await;
+        }
+        if let Some(transaction_id) = user_op.transaction_id.clone() {
+            self.transaction_dao
+                .update_user_transactions_status(
+                    vec![transaction_id],
+                    transaction_status.to_string(),
+                )
+                .await;
+        }
+        true
+    }
+
+    async fn include(
+        &self,
+        user_op: &SubmittedUserOperation,
+        event: &UserOperationEventFilter,
+        meta: &LogMeta,
+    ) {
+        let txn_hash = format!("{:?}", meta.transaction_hash);
+        let revert_reason = if event.success {
+            None
+        } else {
+            self.get_revert_reason(event, meta).await
+        };
+        info!(
+            "User operation {} included in block {}",
+            user_op.user_op_hash, meta.block_number
+        );
+        self.user_operation_dao
+            .include_user_operation(
+                user_op.user_op_hash.clone(),
+                txn_hash.clone(),
+                meta.block_number.as_u64(),
+                format!("{:?}", meta.block_hash),
+                event.success,
+            )
+            .await;
+        if let Some(transaction_id) = user_op.transaction_id.clone() {
+            let receipt = TransactionReceiptMetadata {
+                transaction_hash: txn_hash,
+                gas: Gas {
+                    currency: CONFIG.chains[&self.chain].currency.clone(),
+                    value: event.actual_gas_cost,
+                },
+                gas_erc20: self.get_gas_erc20(event, meta).await,
+                gas_used: event.actual_gas_used.low_u64(),
+                revert_reason,
+            };
+            self.transaction_dao
+                .update_user_transaction(transaction_id, receipt, Status::INCLUDED.to_string())
+                .await;
+        }
+    }
+
+    // operations paid through a token paymaster also record the tokens it charged
+    async fn get_gas_erc20(&self, event: &UserOperationEventFilter, meta: &LogMeta) -> Option<Gas> {
+        let token_paymaster = self
+            .token_paymaster_providers
+            .values()
+            .find(|provider| provider.abi.address() == event.paymaster)?;
+        let receipt = match PROVIDERS[&self.chain]
+            .get_transaction_receipt(meta.transaction_hash)
+            .await
+        {
+            Ok(receipt) => receipt?,
+            Err(err) => {
+                warn!("Get transaction receipt failed: {:?}", err);
+                return None;
+            }
+        };
+        let logs: Vec<Log> = receipt
+            .logs
+            .into_iter()
+            .take_while(|log| log.log_index != Some(meta.log_index))
+            .collect();
+        let token_charge = token_paymaster.get_token_charge(&logs, event.sender)?;
+        Some(Gas {
+            currency: token_paymaster.currency.clone(),
+            value: token_charge,
+        })
+    }
+
+    async fn get_revert_reason(
+        &self,
+        event: &UserOperationEventFilter,
+        meta: &LogMeta,
+    ) -> Option<String> {
+        let revert_reason = self
+            .entrypoint
+            .get_user_operation_revert_reason(H256::from(event.user_op_hash), meta.block_number)
+            .await
+            .ok()?;
+        match revert_reason {
+            Some(revert_reason) => Some(decode_revert_reason(
+                &revert_reason,
+                &[
+                    self.simple_account_provider.abi.abi(),
+                    self.erc20_provider.abi.abi(),
+                ],
+            )),
+            // without a revert reason event the operation ran out of gas or failed in postOp
+            None => Some(String::from("execution failed without a revert reason")),
+        }
+    }
+
+    // Past the deadline an operation is failed if its bundle was mined without it, and
+    // dropped if the bundle never made it on chain.
+    async fn expire(&self, user_op: &SubmittedUserOperation) -> bool {
+        let elapsed = Utc::now() - user_op.submitted_at;
+        if elapsed.num_seconds() < CONFIG.receipt_tracker.deadline_seconds {
+            return false;
+        }
+        let txn_hash = match user_op.transaction_hash.parse::<H256>() {
+            Ok(txn_hash) => txn_hash,
+            Err(_) => {
+                warn!("Invalid bundle hash: {}", user_op.transaction_hash);
+                return false;
+            }
+        };
+        let status = match PROVIDERS[&self.chain]
+            .get_transaction_receipt(txn_hash)
+            .await
+        {
+            Ok(Some(_)) => Status::FAILED,
+            Ok(None) => Status::DROPPED,
+            Err(err) => {
+                warn!(
+                    "Failed to get bundle receipt: {:?}, err: {:?}",
+                    txn_hash, err
+                );
+                return false;
+            }
+        };
+        warn!(
+            "User operation {} timed out and is {}",
+            user_op.user_op_hash,
+            status.to_string()
+        );
+        self.user_operation_dao
+            .update_user_operations(
+                vec![user_op.user_op_hash.clone()],
+                Some(user_op.transaction_hash.clone()),
+                status.to_string(),
+            )
+            .await;
+        self.release(&user_op.user_op_hash).await;
+        if let Some(transaction_id) = user_op.transaction_id.clone() {
+            self.transaction_dao
+                .update_user_transactions_status(vec![transaction_id], status.to_string())
+                .await;
+        }
+        true
+    }
+}
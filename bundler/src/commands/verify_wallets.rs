@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ethers::providers::{Http, Provider};
+use ethers::types::Address;
+use log::{error, info};
+
+use crate::contracts::simple_account_factory_provider::{
+    SimpleAccountFactory, SimpleAccountFactoryProvider,
+};
+use crate::db::connection::DatabaseConnection;
+use crate::db::dao::wallet_dao::WalletDao;
+use crate::provider::helpers::to_u256;
+use crate::server::init_logging;
+use crate::{CONFIG, PROVIDERS};
+
+// Checks every stored wallet address against SimpleAccountFactory.getAddress(owner, salt)
+pub async fn verify_wallets() -> bool {
+    init_logging();
+    let wallet_dao = WalletDao {
+        pool: DatabaseConnection::init().await,
+    };
+    let factories: HashMap<&String, SimpleAccountFactory<Provider<Http>>> = CONFIG
+        .chains
+        .keys()
+        .map(|chain| {
+            let client = Arc::new(PROVIDERS[chain].clone());
+            (chain, SimpleAccountFactoryProvider::init_abi(chain, client))
+        })
+        .collect();
+
+    let users = wallet_dao.get_wallets().await;
+    let mut mismatches = 0;
+    for user in &users {
+        let chain = user
+            .chain
+            .clone()
+            .unwrap_or(CONFIG.run_config.current_chain.clone());
+        let factory = match factories.get(&chain) {
+            Some(factory) => factory,
+            None => {
+                error!("Unknown chain {} for {}", chain, user.email);
+                mismatches += 1;
+                continue;
+            }
+        };
+        let owner: Address = match &user.owner_address {
+            Some(owner_address) => match owner_address.parse() {
+                Ok(owner) => owner,
+                Err(err) => {
+                    error!(
+                        "Invalid owner address for {}: {}, err: {:?}",
+                        user.email, owner_address, err
+                    );
+                    mismatches += 1;
+                    continue;
+                }
+            },
+            None => CONFIG.run_config.account_owner,
+        };
+        let salt = match to_u256(&user.salt) {
+            Ok(salt) => salt,
+            Err(err) => {
+                error!("Invalid salt for {}: {}", user.email, err);
+                mismatches += 1;
+                continue;
+            }
+        };
+        match factory.get_address(owner, salt).call().await {
+            Ok(address) if format!("{:?}", address) == user.wallet_address => {}
+            Ok(address) => {
+                error!(
+                    "Wallet mismatch for {} on {} (salt scheme v{}): stored {}, factory {:?}",
+                    user.email, chain, user.salt_scheme_version, user.wallet_address, address
+                );
+                mismatches += 1;
+            }
+            Err(err) => {
+                error!(
+                    "Failed to get address for {} on {}: {:?}",
+                    user.email, chain, err
+                );
+                mismatches += 1;
+            }
+        }
+    }
+    info!(
+        "Verified {} wallets, {} mismatches",
+        users.len(),
+        mismatches
+    );
+    mismatches == 0
+}
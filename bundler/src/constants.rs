@@ -8,4 +8,42 @@ impl Constants {
 
     // Currency
     pub const NATIVE: &'static str = "native";
+    pub const NATIVE_EXPONENT: i32 = 18;
+
+    // Batch transfers
+    pub const MAX_BATCH_TRANSFERS: usize = 50;
+
+    // Idempotency
+    pub const IDEMPOTENCY_KEY_HEADER: &'static str = "Idempotency-Key";
+    pub const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+    pub const IDEMPOTENCY_RESERVATION_MINUTES: i32 = 5;
+
+    // Wallet salt
+    pub const SALT_NAMESPACE: &'static str = "toad.wallet.salt";
+    pub const SALT_SCHEME_VERSION: i32 = 1;
+
+    // Json RPC
+    pub const USER_OPERATION_LOOKUP_BLOCKS: u64 = 5000;
+
+    // Validation
+    pub const INVALID_NONCE_CODE: &'static str = "AA25";
+    pub const ENTRYPOINT_NONCE_SLOT: u64 = 1;
+    pub const MIN_VALIDITY_SECONDS: u64 = 30;
+
+    // Rate limits
+    pub const MINUTE_SECONDS: u64 = 60;
+    pub const DAY_SECONDS: u64 = 86400;
+    pub const MAX_IN_MEMORY_RATE_LIMIT_KEYS: usize = 10000;
+
+    // Revert reasons
+    pub const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+    pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+    // Gas overheads
+    pub const FIXED_BUNDLE_GAS: u64 = 21000;
+    pub const PER_USER_OP_GAS: u64 = 18300;
+    pub const PER_USER_OP_WORD_GAS: u64 = 4;
+    pub const ZERO_BYTE_GAS: u64 = 4;
+    pub const NON_ZERO_BYTE_GAS: u64 = 16;
+    pub const DUMMY_SIGNATURE_SIZE: usize = 65;
 }
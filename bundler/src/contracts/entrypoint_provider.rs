@@ -1,9 +1,13 @@
+use crate::constants::Constants;
 use crate::models::contract_interaction;
+use crate::models::contract_interaction::user_operation_error::UserOperationError;
 use crate::CONFIG;
-use ethers::abi::Abi;
-use ethers::contract::abigen;
-use ethers::providers::{Http, Provider};
-use ethers::types::{Address, Bytes, U256};
+use ethers::abi::{encode, Abi, AbiDecode, Token};
+use ethers::contract::{abigen, LogMeta};
+use ethers::providers::{spoof, Http, Middleware, Provider, RawCall, RpcError};
+use ethers::types::{Address, Bytes, H256, U256, U64};
+use ethers::utils::keccak256;
+use log::error;
 use std::sync::Arc;
 
 abigen!(EntryPoint, "abi/Entrypoint.json");
@@ -34,6 +38,15 @@ impl EntryPointProvider {
         Ok(result.unwrap())
     }
 
+    pub async fn get_deposit_info(&self, address: Address) -> Result<DepositInfo, String> {
+        let result = self.abi.get_deposit_info(address).await;
+        if result.is_err() {
+            error!("Get deposit info failed: {:?}", result.err().unwrap());
+            return Err(String::from("failed to get deposit info"));
+        }
+        Ok(result.unwrap())
+    }
+
     pub async fn add_deposit(&self, address: Address) -> Result<Bytes, String> {
         let data = self.abi.deposit_to(address).calldata();
         if data.is_none() {
@@ -44,13 +57,16 @@ impl EntryPointProvider {
 
     pub async fn handle_ops(
         &self,
-        user_op: contract_interaction::user_operation::UserOperation,
+        user_ops: Vec<contract_interaction::user_operation::UserOperation>,
         beneficiary: Address,
     ) -> Result<Bytes, String> {
         let data = self
             .abi
             .handle_ops(
-                vec![self.get_entry_point_user_operation_payload(user_op)],
+                user_ops
+                    .into_iter()
+                    .map(|user_op| self.get_entry_point_user_operation_payload(user_op))
+                    .collect(),
                 beneficiary,
             )
             .calldata();
@@ -60,7 +76,238 @@ impl EntryPointProvider {
         Ok(data.unwrap())
     }
 
-    fn get_entry_point_user_operation_payload(
+    pub async fn simulate_validation(
+        &self,
+        user_op: contract_interaction::user_operation::UserOperation,
+    ) -> Result<ValidationResult, UserOperationError> {
+        let result = self
+            .abi
+            .simulate_validation(self.get_entry_point_user_operation_payload(user_op))
+            .call()
+            .await;
+        match result {
+            Ok(_) => Err(UserOperationError::new(
+                UserOperationError::REJECTED_BY_ENTRYPOINT,
+                String::from("simulate validation did not revert"),
+            )),
+            Err(err) => Self::get_validation_result(err.decode_contract_revert(), err.to_string()),
+        }
+    }
+
+    // Simulates an operation queued behind its sender's pending ones, as if those had already
+    // advanced the sender's nonce to the operation's own. Everything else is validated as is.
+    pub async fn simulate_validation_queued(
+        &self,
+        user_op: contract_interaction::user_operation::UserOperation,
+    ) -> Result<ValidationResult, UserOperationError> {
+        let mut state = spoof::state();
+        state.account(self.abi.address()).store(
+            Self::get_nonce_slot(user_op.sender),
+            H256::from_low_u64_be(user_op.nonce),
+        );
+        let call = self
+            .abi
+            .simulate_validation(self.get_entry_point_user_operation_payload(user_op));
+        let result = call.call_raw_bytes().state(&state).await;
+        match result {
+            Ok(_) => Err(UserOperationError::new(
+                UserOperationError::REJECTED_BY_ENTRYPOINT,
+                String::from("simulate validation did not revert"),
+            )),
+            Err(err) => Self::get_validation_result(
+                err.as_error_response()
+                    .and_then(|response| response.as_revert_data())
+                    .and_then(|data| EntryPointErrors::decode(data).ok()),
+                err.to_string(),
+            ),
+        }
+    }
+
+    fn get_validation_result(
+        revert: Option<EntryPointErrors>,
+        err: String,
+    ) -> Result<ValidationResult, UserOperationError> {
+        match revert {
+            Some(EntryPointErrors::ValidationResult(validation_result)) => Ok(validation_result),
+            Some(EntryPointErrors::FailedOp(failed_op)) => {
+                Err(UserOperationError::from_failed_op(failed_op.reason))
+            }
+            _ => {
+                error!("EntryPoint: Simulate validation: {:?}", err);
+                Err(UserOperationError::new(
+                    UserOperationError::REJECTED_BY_ENTRYPOINT,
+                    String::from("user operation simulation failed"),
+                ))
+            }
+        }
+    }
+
+    // nonceSequenceNumber[sender][0] of the EntryPoint's NonceManager
+    fn get_nonce_slot(sender: Address) -> H256 {
+        let sender_slot = keccak256(encode(&[
+            Token::Address(sender),
+            Token::Uint(U256::from(Constants::ENTRYPOINT_NONCE_SLOT)),
+        ]));
+        H256::from(keccak256(encode(&[
+            Token::Uint(U256::zero()),
+            Token::FixedBytes(sender_slot.to_vec()),
+        ])))
+    }
+
+    pub async fn simulate_handle_op(
+        &self,
+        user_op: contract_interaction::user_operation::UserOperation,
+    ) -> Result<ExecutionResult, UserOperationError> {
+        let result = self
+            .abi
+            .simulate_handle_op(
+                self.get_entry_point_user_operation_payload(user_op),
+                Address::zero(),
+                Bytes::default(),
+            )
+            .call()
+            .await;
+        let err = match result {
+            Ok(_) => {
+                return Err(UserOperationError::new(
+                    UserOperationError::REJECTED_BY_ENTRYPOINT,
+                    String::from("simulate handle op did not revert"),
+                ))
+            }
+            Err(err) => err,
+        };
+        match err.decode_contract_revert::<EntryPointErrors>() {
+            Some(EntryPointErrors::ExecutionResult(execution_result)) => Ok(execution_result),
+            Some(EntryPointErrors::FailedOp(failed_op)) => {
+                Err(UserOperationError::from_failed_op(failed_op.reason))
+            }
+            _ => {
+                error!("EntryPoint: Simulate handle op: {:?}", err.to_string());
+                Err(UserOperationError::new(
+                    UserOperationError::REJECTED_BY_ENTRYPOINT,
+                    String::from("user operation simulation failed"),
+                ))
+            }
+        }
+    }
+
+    pub async fn get_user_operation_events(
+        &self,
+        user_op_hashes: Vec<H256>,
+        from_block: u64,
+    ) -> Result<Vec<(UserOperationEventFilter, LogMeta)>, String> {
+        let result = self
+            .abi
+            .user_operation_event_filter()
+            .topic1(user_op_hashes)
+            .from_block(from_block)
+            .query_with_meta()
+            .await;
+        match result {
+            Ok(events) => Ok(events),
+            Err(err) => {
+                error!("EntryPoint: UserOperationEvents: {:?}", err.to_string());
+                Err(String::from("failed to get user operation events"))
+            }
+        }
+    }
+
+    pub async fn get_user_operation_revert_reason(
+        &self,
+        user_op_hash: H256,
+        block_number: U64,
+    ) -> Result<Option<Bytes>, String> {
+        let result = self
+            .abi
+            .user_operation_revert_reason_filter()
+            .topic1(user_op_hash)
+            .from_block(block_number)
+            .to_block(block_number)
+            .query()
+            .await;
+        match result {
+            Ok(events) => Ok(events.into_iter().next().map(|event| event.revert_reason)),
+            Err(err) => {
+                error!(
+                    "EntryPoint: UserOperationRevertReason: {:?}",
+                    err.to_string()
+                );
+                Err(String::from("failed to get user operation revert reason"))
+            }
+        }
+    }
+
+    pub async fn get_user_operation_event(
+        &self,
+        user_op_hash: H256,
+    ) -> Result<Option<(UserOperationEventFilter, LogMeta)>, String> {
+        let latest_block = self.abi.client().get_block_number().await;
+        if latest_block.is_err() {
+            error!(
+                "EntryPoint: Block number: {:?}",
+                latest_block.err().unwrap().to_string()
+            );
+            return Err(String::from("failed to get block number"));
+        }
+        let from_block = latest_block
+            .unwrap()
+            .saturating_sub(Constants::USER_OPERATION_LOOKUP_BLOCKS.into());
+        let result = self
+            .abi
+            .user_operation_event_filter()
+            .topic1(user_op_hash)
+            .from_block(from_block)
+            .query_with_meta()
+            .await;
+        match result {
+            Ok(events) => Ok(events.into_iter().next()),
+            Err(err) => {
+                error!("EntryPoint: UserOperationEvent: {:?}", err.to_string());
+                Err(String::from("failed to get user operation event"))
+            }
+        }
+    }
+
+    pub fn decode_handle_ops(
+        &self,
+        data: &Bytes,
+    ) -> Result<Vec<contract_interaction::user_operation::UserOperation>, String> {
+        let call = HandleOpsCall::decode(data);
+        if call.is_err() {
+            return Err(String::from("handle ops decode failed"));
+        }
+        call.unwrap()
+            .ops
+            .into_iter()
+            .map(Self::get_user_operation_from_payload)
+            .collect()
+    }
+
+    fn get_user_operation_from_payload(
+        user_op: UserOperation,
+    ) -> Result<contract_interaction::user_operation::UserOperation, String> {
+        let to_u64 = |value: U256| -> Result<u64, String> {
+            if value > U256::from(u64::MAX) {
+                return Err(String::from("user operation field overflow"));
+            }
+            Ok(value.as_u64())
+        };
+        Ok(contract_interaction::user_operation::UserOperation {
+            sender: user_op.sender,
+            nonce: to_u64(user_op.nonce)?,
+            init_code: user_op.init_code,
+            calldata: user_op.call_data,
+            call_gas_limit: to_u64(user_op.call_gas_limit)?,
+            verification_gas_limit: to_u64(user_op.verification_gas_limit)?,
+            pre_verification_gas: to_u64(user_op.pre_verification_gas)?,
+            max_fee_per_gas: to_u64(user_op.max_fee_per_gas)?,
+            max_priority_fee_per_gas: to_u64(user_op.max_priority_fee_per_gas)?,
+            paymaster_and_data: user_op.paymaster_and_data,
+            signature: user_op.signature,
+        })
+    }
+
+    pub fn get_entry_point_user_operation_payload(
         &self,
         user_op: contract_interaction::user_operation::UserOperation,
     ) -> UserOperation {
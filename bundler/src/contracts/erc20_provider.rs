@@ -3,16 +3,17 @@ use ethers::abi::Address;
 use ethers::contract::abigen;
 use ethers::providers::{Http, Provider};
 use ethers::types::{Bytes, U256};
+use log::error;
 use std::sync::Arc;
 
 abigen!(ERC20, "abi/ERC20.json");
 
 #[derive(Clone)]
-pub struct USDCProvider {
+pub struct Erc20Provider {
     pub abi: ERC20<Provider<Http>>,
 }
 
-impl USDCProvider {
+impl Erc20Provider {
     pub fn init_abi(current_chain: &str, client: Arc<Provider<Http>>) -> ERC20<Provider<Http>> {
         let contract: ERC20<Provider<Http>> =
             ERC20::new(CONFIG.chains[current_chain].usdc_address, client);
@@ -30,4 +31,25 @@ impl USDCProvider {
 
         Ok(data.unwrap())
     }
+
+    pub fn approve(&self, spender: Address, value: U256) -> Result<Bytes, String> {
+        let data = self.abi.approve(spender, value).calldata();
+        if data.is_none() {
+            return Err("approve data failed".to_string());
+        }
+
+        Ok(data.unwrap())
+    }
+
+    pub async fn allowance(&self, owner: Address, spender: Address) -> Result<U256, String> {
+        let response = self.abi.allowance(owner, spender).call().await;
+        if response.is_err() {
+            error!(
+                "ERC20: Allowance: {:?}",
+                response.err().unwrap().to_string()
+            );
+            return Err(String::from("Failed to get allowance"));
+        }
+        Ok(response.unwrap())
+    }
 }
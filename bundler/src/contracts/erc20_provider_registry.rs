@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use ethers::providers::{Http, Provider};
+use ethers::types::Address;
+
+use crate::contracts::erc20_provider::{Erc20Provider, ERC20};
+
+// ERC20 providers keyed by token_metadata.contract_address, created on first use
+#[derive(Clone)]
+pub struct Erc20ProviderRegistry {
+    pub client: Arc<Provider<Http>>,
+    providers: Arc<RwLock<HashMap<Address, Erc20Provider>>>,
+}
+
+impl Erc20ProviderRegistry {
+    pub fn new(client: Arc<Provider<Http>>) -> Erc20ProviderRegistry {
+        Erc20ProviderRegistry {
+            client,
+            providers: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn get(&self, contract_address: &str) -> Result<Erc20Provider, String> {
+        let address: Address = contract_address
+            .parse()
+            .map_err(|_| format!("Invalid token contract address: {}", contract_address))?;
+        if let Some(provider) = self.providers.read().unwrap().get(&address) {
+            return Ok(provider.clone());
+        }
+        let provider = Erc20Provider {
+            abi: ERC20::new(address, self.client.clone()),
+        };
+        self.providers
+            .write()
+            .unwrap()
+            .insert(address, provider.clone());
+        Ok(provider)
+    }
+}
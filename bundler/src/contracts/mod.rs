@@ -1,4 +1,6 @@
 pub mod entrypoint_provider;
+pub mod erc20_provider;
+pub mod erc20_provider_registry;
 pub mod simple_account_factory_provider;
 pub mod simple_account_provider;
-pub mod usdc_provider;
+pub mod token_paymaster_provider;
@@ -31,4 +31,13 @@ impl SimpleAccountProvider {
 
         Ok(data.unwrap())
     }
+
+    pub fn execute_batch(&self, to: Vec<Address>, data: Vec<Bytes>) -> Result<Bytes, String> {
+        let data = self.abi.execute_batch(to, data).calldata();
+        if data.is_none() {
+            return Err("execute batch data failed".to_string());
+        }
+
+        Ok(data.unwrap())
+    }
 }
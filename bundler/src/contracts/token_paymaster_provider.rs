@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ethers::abi::RawLog;
+use ethers::contract::{abigen, EthLogDecode};
+use ethers::providers::{Http, Provider};
+use ethers::types::{Address, Bytes, Log, U256};
+use log::error;
+
+use crate::CONFIG;
+
+abigen!(TokenPaymaster, "abi/TokenPaymaster.json");
+
+#[derive(Clone)]
+pub struct TokenPaymasterProvider {
+    pub currency: String,
+    pub abi: TokenPaymaster<Provider<Http>>,
+}
+
+impl TokenPaymasterProvider {
+    // token paymasters of the chain keyed by the currency they charge gas in
+    pub fn init_providers(
+        current_chain: &str,
+        client: Arc<Provider<Http>>,
+    ) -> HashMap<String, TokenPaymasterProvider> {
+        CONFIG.chains[current_chain]
+            .token_paymasters
+            .iter()
+            .map(|(currency, address)| {
+                let provider = TokenPaymasterProvider {
+                    currency: currency.to_lowercase(),
+                    abi: TokenPaymaster::new(*address, client.clone()),
+                };
+                (provider.currency.clone(), provider)
+            })
+            .collect()
+    }
+
+    // without a client supplied price the paymaster charges at its cached price
+    pub fn get_paymaster_and_data(&self) -> Bytes {
+        Bytes::from(self.abi.address().as_bytes().to_vec())
+    }
+
+    pub async fn get_token(&self) -> Result<Address, String> {
+        let response = self.abi.token().call().await;
+        if response.is_err() {
+            error!(
+                "Token paymaster: Token: {:?}",
+                response.err().unwrap().to_string()
+            );
+            return Err(String::from("Failed to get paymaster token"));
+        }
+        Ok(response.unwrap())
+    }
+
+    // postOp reports the charge right before the UserOperationEvent of the same operation
+    pub fn get_token_charge(&self, logs: &[Log], sender: Address) -> Option<U256> {
+        logs.iter()
+            .rev()
+            .filter(|log| log.address == self.abi.address())
+            .find_map(|log| {
+                let event = UserOperationSponsoredFilter::decode_log(&RawLog::from(log.clone()));
+                match event {
+                    Ok(event) if event.user == sender => Some(event.actual_token_charge),
+                    _ => None,
+                }
+            })
+    }
+}
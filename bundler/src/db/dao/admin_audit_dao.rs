@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use log::error;
+use serde_json::Value;
+use sqlx::{query, query_as, Pool, Postgres};
+
+#[derive(Clone)]
+pub struct AdminAuditDao {
+    pub pool: Pool<Postgres>,
+}
+
+impl AdminAuditDao {
+    pub async fn create_entry(&self, entry: AdminAuditEntry) {
+        let query = query!(
+            "INSERT INTO admin_audit_log (actor, client, action, parameters, result, error, \
+            transaction_hash) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            entry.actor,
+            entry.client,
+            entry.action,
+            entry.parameters,
+            entry.result,
+            entry.error,
+            entry.transaction_hash
+        );
+        let result = query.execute(&self.pool).await;
+        if result.is_err() {
+            error!(
+                "Failed to record admin action: {} by {}, err: {:?}",
+                entry.action,
+                entry.actor,
+                result.err()
+            );
+        }
+    }
+
+    pub async fn list_entries(
+        &self,
+        page_size: i64,
+        id: i32,
+        actor: Option<String>,
+        action: Option<String>,
+    ) -> Vec<AdminAuditEntry> {
+        let query = query_as!(
+            AdminAuditEntry,
+            "SELECT id, actor, client, action, parameters, result, error, transaction_hash, created_at \
+            FROM admin_audit_log WHERE id < $1 and ($2::VARCHAR is null or actor = $2) \
+            and ($3::VARCHAR is null or action = $3) order by id desc limit $4",
+            id,
+            actor,
+            action,
+            page_size
+        );
+        let result = query.fetch_all(&self.pool).await;
+        match result {
+            Ok(rows) => rows,
+            Err(err) => {
+                error!("Failed to fetch admin audit log: {:?}", err);
+                vec![]
+            }
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct AdminAuditEntry {
+    pub id: i32,
+    pub actor: String,
+    pub client: Option<String>,
+    pub action: String,
+    pub parameters: Value,
+    pub result: String,
+    pub error: Option<String>,
+    pub transaction_hash: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
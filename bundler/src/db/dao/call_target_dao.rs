@@ -0,0 +1,87 @@
+use log::error;
+use sqlx::{query, query_as, Error, Pool, Postgres};
+
+#[derive(Clone)]
+pub struct CallTargetDao {
+    pub pool: Pool<Postgres>,
+}
+
+impl CallTargetDao {
+    pub async fn add_call_target(&self, chain: String, address: String, name: String) {
+        let query = query!(
+            "INSERT INTO call_targets (chain, address, name) VALUES ($1, $2, $3) \
+            on conflict (chain, address) do update set name = $3, updated_at = now()",
+            chain,
+            address,
+            name
+        );
+        let result = query.execute(&self.pool).await;
+        if result.is_err() {
+            error!(
+                "Failed to add call target: {} on {}, err: {:?}",
+                address,
+                chain,
+                result.err()
+            );
+        }
+    }
+
+    pub async fn remove_call_target(&self, chain: String, address: String) {
+        let query = query!(
+            "DELETE FROM call_targets WHERE chain = $1 and address = $2",
+            chain,
+            address
+        );
+        let result = query.execute(&self.pool).await;
+        if result.is_err() {
+            error!(
+                "Failed to remove call target: {} on {}, err: {:?}",
+                address,
+                chain,
+                result.err()
+            );
+        }
+    }
+
+    pub async fn get_call_targets(&self, chain: String) -> Vec<CallTarget> {
+        let query = query_as!(
+            CallTarget,
+            "SELECT address, name FROM call_targets WHERE chain = $1 order by created_at",
+            chain
+        );
+        let result: Result<Vec<CallTarget>, Error> = query.fetch_all(&self.pool).await;
+        match result {
+            Ok(call_targets) => call_targets,
+            Err(err) => {
+                error!("Failed to get call targets for {}, err: {:?}", chain, err);
+                vec![]
+            }
+        }
+    }
+
+    pub async fn is_allowed(&self, chain: String, address: String) -> bool {
+        let query = query_as!(
+            CallTarget,
+            "SELECT address, name FROM call_targets WHERE chain = $1 and address = $2",
+            chain,
+            address
+        );
+        let result: Result<Option<CallTarget>, Error> = query.fetch_optional(&self.pool).await;
+        match result {
+            Ok(call_target) => call_target.is_some(),
+            Err(err) => {
+                error!(
+                    "Failed to check call target: {} on {}, err: {:?}",
+                    address, chain, err
+                );
+                false
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CallTarget {
+    pub address: String,
+    pub name: String,
+}
@@ -0,0 +1,104 @@
+use log::error;
+use sqlx::{query, query_as, Pool, Postgres};
+
+use crate::constants::Constants;
+
+#[derive(Clone)]
+pub struct IdempotencyKeyDao {
+    pub pool: Pool<Postgres>,
+}
+
+impl IdempotencyKeyDao {
+    // Claims the key for the request, or returns the request that claimed it first. A claim
+    // that was never completed or released, say by a crashed instance, lapses after a while.
+    pub async fn reserve(
+        &self,
+        user_id: String,
+        idempotency_key: String,
+        request_hash: String,
+    ) -> Result<Option<IdempotencyKey>, String> {
+        let query = query!(
+            "INSERT INTO idempotency_keys (user_id, idempotency_key, request_hash) \
+            VALUES ($1, $2, $3) on conflict (user_id, idempotency_key) do update set \
+            request_hash = $3, reserved_at = now(), updated_at = now() \
+            where idempotency_keys.transaction_id is null \
+            and idempotency_keys.reserved_at < now() - make_interval(mins => $4)",
+            user_id,
+            idempotency_key,
+            request_hash,
+            Constants::IDEMPOTENCY_RESERVATION_MINUTES
+        );
+        match query.execute(&self.pool).await {
+            Ok(result) if result.rows_affected() == 1 => return Ok(None),
+            Ok(_) => {}
+            Err(err) => {
+                error!(
+                    "Failed to reserve idempotency key: {} for {}, err: {:?}",
+                    idempotency_key, user_id, err
+                );
+                return Err(String::from("Failed to reserve idempotency key"));
+            }
+        }
+        let query = query_as!(
+            IdempotencyKey,
+            "SELECT request_hash, transaction_id FROM idempotency_keys \
+            WHERE user_id = $1 and idempotency_key = $2",
+            user_id,
+            idempotency_key
+        );
+        match query.fetch_one(&self.pool).await {
+            Ok(row) => Ok(Some(row)),
+            Err(err) => {
+                error!(
+                    "Failed to get idempotency key: {} for {}, err: {:?}",
+                    idempotency_key, user_id, err
+                );
+                Err(String::from("Failed to get idempotency key"))
+            }
+        }
+    }
+
+    pub async fn complete(&self, user_id: String, idempotency_key: String, transaction_id: String) {
+        let query = query!(
+            "UPDATE idempotency_keys set transaction_id = $1, updated_at = now() \
+            where user_id = $2 and idempotency_key = $3",
+            transaction_id,
+            user_id,
+            idempotency_key
+        );
+        let result = query.execute(&self.pool).await;
+        if result.is_err() {
+            error!(
+                "Failed to complete idempotency key: {} for {}, err: {:?}",
+                idempotency_key,
+                user_id,
+                result.err()
+            );
+        }
+    }
+
+    // a request that failed leaves nothing to replay, so its key can be retried
+    pub async fn release(&self, user_id: String, idempotency_key: String) {
+        let query = query!(
+            "DELETE FROM idempotency_keys where user_id = $1 and idempotency_key = $2 \
+            and transaction_id is null",
+            user_id,
+            idempotency_key
+        );
+        let result = query.execute(&self.pool).await;
+        if result.is_err() {
+            error!(
+                "Failed to release idempotency key: {} for {}, err: {:?}",
+                idempotency_key,
+                user_id,
+                result.err()
+            );
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct IdempotencyKey {
+    pub request_hash: String,
+    pub transaction_id: Option<String>,
+}
@@ -1,3 +1,10 @@
+pub mod admin_audit_dao;
+pub mod call_target_dao;
+pub mod idempotency_key_dao;
+pub mod paymaster_top_up_dao;
+pub mod rate_limit_dao;
+pub mod sponsorship_dao;
 pub mod token_metadata_dao;
 pub mod transaction_dao;
+pub mod user_operation_dao;
 pub mod wallet_dao;
@@ -0,0 +1,52 @@
+use log::error;
+use sqlx::{query, Pool, Postgres};
+
+#[derive(Clone)]
+pub struct PaymasterTopUpDao {
+    pub pool: Pool<Postgres>,
+}
+
+impl PaymasterTopUpDao {
+    pub async fn create_top_up(
+        &self,
+        chain: String,
+        paymaster: String,
+        amount_gwei: u64,
+        transaction_hash: String,
+    ) {
+        let query = query!(
+            "INSERT INTO paymaster_top_ups (chain, paymaster, amount_gwei, transaction_hash) \
+            VALUES ($1, $2, $3, $4)",
+            chain,
+            paymaster,
+            amount_gwei as i64,
+            transaction_hash
+        );
+        let result = query.execute(&self.pool).await;
+        if result.is_err() {
+            error!(
+                "Failed to create paymaster top-up: {}, err: {:?}",
+                transaction_hash,
+                result.err()
+            );
+        }
+    }
+
+    pub async fn get_top_up_total(&self, chain: String) -> Result<i64, String> {
+        let query = query!(
+            "SELECT COALESCE(SUM(amount_gwei), 0)::BIGINT as \"total!\" FROM paymaster_top_ups \
+            WHERE chain = $1 and created_at > now() - interval '1 day'",
+            chain
+        );
+        match query.fetch_one(&self.pool).await {
+            Ok(row) => Ok(row.total),
+            Err(err) => {
+                error!(
+                    "Failed to get paymaster top-ups for {}, err: {:?}",
+                    chain, err
+                );
+                Err(String::from("Failed to get paymaster top-ups"))
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use bigdecimal::BigDecimal;
+use log::error;
+use sqlx::{query, Pool, Postgres};
+
+#[derive(Clone)]
+pub struct RateLimitDao {
+    pub pool: Pool<Postgres>,
+}
+
+#[derive(Clone)]
+pub struct RateLimitCounter {
+    pub key: String,
+    pub window_start: u64,
+    pub amount: BigDecimal,
+    pub limit: BigDecimal,
+}
+
+impl RateLimitDao {
+    // Adds each amount to its key's total for the window, unless that takes one of them past
+    // its limit, in which case none is added and the index of that counter is returned.
+    // Counters from an earlier window start over.
+    pub async fn acquire(&self, counters: &[RateLimitCounter]) -> Result<Option<usize>, String> {
+        self.try_acquire(counters).await.map_err(|err| {
+            error!("Failed to update rate limit counters, err: {:?}", err);
+            String::from("Failed to update rate limit counters")
+        })
+    }
+
+    async fn try_acquire(
+        &self,
+        counters: &[RateLimitCounter],
+    ) -> Result<Option<usize>, sqlx::Error> {
+        // counters are locked in key order so concurrent acquisitions cannot deadlock
+        let mut order: Vec<usize> = (0..counters.len()).collect();
+        order.sort_by(|a, b| counters[*a].key.cmp(&counters[*b].key));
+        let mut txn = self.pool.begin().await?;
+        for index in order {
+            let counter = &counters[index];
+            let row = query!(
+                "INSERT INTO rate_limit_counters (key, window_start, total) VALUES ($1, $2, $3) \
+                on conflict (key) do update set total = case \
+                when rate_limit_counters.window_start = $2 then rate_limit_counters.total + $3 \
+                else $3 end, window_start = $2, updated_at = now() \
+                where rate_limit_counters.window_start <> $2 \
+                or rate_limit_counters.total + $3 <= $4 \
+                RETURNING total",
+                counter.key,
+                counter.window_start as i64,
+                counter.amount,
+                counter.limit
+            )
+            .fetch_optional(&mut *txn)
+            .await?;
+            if row.is_none() {
+                txn.rollback().await?;
+                return Ok(Some(index));
+            }
+        }
+        txn.commit().await?;
+        Ok(None)
+    }
+
+    // gives back what was acquired, as long as the counters are still in the same window
+    pub async fn release(&self, counters: &[RateLimitCounter]) {
+        let keys: Vec<String> = counters.iter().map(|c| c.key.clone()).collect();
+        let window_starts: Vec<i64> = counters.iter().map(|c| c.window_start as i64).collect();
+        let amounts: Vec<BigDecimal> = counters.iter().map(|c| c.amount.clone()).collect();
+        let query = query!(
+            "UPDATE rate_limit_counters r set total = greatest(r.total - c.amount, 0), \
+            updated_at = now() from unnest($1::varchar[], $2::bigint[], $3::numeric[]) \
+            as c(key, window_start, amount) where r.key = c.key and r.window_start = c.window_start",
+            &keys[..],
+            &window_starts[..],
+            &amounts[..]
+        );
+        let result = query.execute(&self.pool).await;
+        if result.is_err() {
+            error!(
+                "Failed to release rate limit counters: {:?}, err: {:?}",
+                keys,
+                result.err()
+            );
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use bigdecimal::BigDecimal;
+use log::error;
+use sqlx::{query, Pool, Postgres};
+
+use crate::models::transfer::status::Status;
+
+#[derive(Clone)]
+pub struct SponsorshipDao {
+    pub pool: Pool<Postgres>,
+}
+
+impl SponsorshipDao {
+    // Budgets are checked and reserved under a per-chain lock, so concurrent sponsorships
+    // cannot all fit into what is left of them. The reservation counts against the budgets
+    // until it is released or recorded under its user operation hash.
+    pub async fn reserve_sponsorship(
+        &self,
+        sponsorship: &Sponsorship,
+        user_daily_gas_budget: u64,
+        spend_cap: &BigDecimal,
+    ) -> Result<Reservation, String> {
+        let result = self
+            .try_reserve_sponsorship(sponsorship, user_daily_gas_budget, spend_cap)
+            .await;
+        result.map_err(|err| {
+            error!(
+                "Failed to reserve sponsorship for {}, err: {:?}",
+                sponsorship.sender, err
+            );
+            String::from("Failed to reserve sponsorship")
+        })
+    }
+
+    async fn try_reserve_sponsorship(
+        &self,
+        sponsorship: &Sponsorship,
+        user_daily_gas_budget: u64,
+        spend_cap: &BigDecimal,
+    ) -> Result<Reservation, sqlx::Error> {
+        let mut txn = self.pool.begin().await?;
+        query!(
+            "SELECT pg_advisory_xact_lock(hashtext('sponsorships:' || $1::text))",
+            sponsorship.chain
+        )
+        .execute(&mut *txn)
+        .await?;
+        let sender_gas = query!(
+            "SELECT COALESCE(SUM(s.gas_limit), 0)::BIGINT as \"gas!\" FROM sponsorships s \
+            LEFT JOIN user_operations u on u.user_op_hash = s.user_op_hash \
+            WHERE s.chain = $1 and s.sender = $2 and s.created_at > now() - interval '1 day' \
+            and not (u.block_number is null and coalesce(u.status = ANY($3), false))",
+            sponsorship.chain,
+            sponsorship.sender,
+            &[Status::FAILED.to_string(), Status::DROPPED.to_string()][..]
+        )
+        .fetch_one(&mut *txn)
+        .await?
+        .gas;
+        if sender_gas as u64 + sponsorship.gas_limit > user_daily_gas_budget {
+            return Ok(Reservation::GasBudgetExhausted);
+        }
+        let spend = query!(
+            "SELECT COALESCE(SUM(s.max_cost), 0) as \"spend!\" FROM sponsorships s \
+            LEFT JOIN user_operations u on u.user_op_hash = s.user_op_hash \
+            WHERE s.chain = $1 and s.created_at > now() - interval '1 day' \
+            and not (u.block_number is null and coalesce(u.status = ANY($2), false))",
+            sponsorship.chain,
+            &[Status::FAILED.to_string(), Status::DROPPED.to_string()][..]
+        )
+        .fetch_one(&mut *txn)
+        .await?
+        .spend;
+        if spend + &sponsorship.max_cost > *spend_cap {
+            return Ok(Reservation::SpendCapReached);
+        }
+        query!(
+            "INSERT INTO sponsorships (user_op_hash, chain, sender, gas_limit, max_cost) \
+            VALUES ($1, $2, $3, $4, $5)",
+            sponsorship.reservation,
+            sponsorship.chain,
+            sponsorship.sender,
+            sponsorship.gas_limit as i64,
+            sponsorship.max_cost
+        )
+        .execute(&mut *txn)
+        .await?;
+        txn.commit().await?;
+        Ok(Reservation::Reserved)
+    }
+
+    // a sponsorship of the same user operation again replaces the earlier one
+    pub async fn record_sponsorship(&self, reservation: String, user_op_hash: String) {
+        let query = query!(
+            "WITH reserved AS (DELETE FROM sponsorships where user_op_hash = $1 \
+            returning chain, sender, gas_limit, max_cost, created_at) \
+            INSERT INTO sponsorships (user_op_hash, chain, sender, gas_limit, max_cost, created_at) \
+            SELECT $2, chain, sender, gas_limit, max_cost, created_at from reserved \
+            on conflict (user_op_hash) do update set \
+            gas_limit = excluded.gas_limit, max_cost = excluded.max_cost",
+            reservation,
+            user_op_hash,
+        );
+        let result = query.execute(&self.pool).await;
+        if result.is_err() {
+            error!(
+                "Failed to record sponsorship: {}, err: {:?}",
+                user_op_hash,
+                result.err()
+            );
+        }
+    }
+
+    pub async fn release_sponsorship(&self, reservation: String) {
+        let query = query!(
+            "DELETE FROM sponsorships where user_op_hash = $1",
+            reservation
+        );
+        let result = query.execute(&self.pool).await;
+        if result.is_err() {
+            error!(
+                "Failed to release sponsorship: {}, err: {:?}",
+                reservation,
+                result.err()
+            );
+        }
+    }
+}
+
+pub struct Sponsorship {
+    pub reservation: String,
+    pub chain: String,
+    pub sender: String,
+    pub gas_limit: u64,
+    pub max_cost: BigDecimal,
+}
+
+pub enum Reservation {
+    Reserved,
+    GasBudgetExhausted,
+    SpendCapReached,
+}
@@ -1,5 +1,6 @@
 use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
+use ethers::types::U256;
 use log::error;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -23,7 +24,8 @@ impl TransactionDao {
             UserTransaction,
             "SELECT t1.id, t1.user_address, t1.transaction_id, t1.from_address, t1.to_address, \
             t1.amount, t1.currency, t1.type as transaction_type, t1.status, t1.metadata, \
-            t1.created_at, t1.updated_at, t2.exponent from user_transactions t1 left join \
+            t1.created_at, t1.updated_at, t2.exponent, t1.batch_id from user_transactions t1 \
+            left join \
             token_metadata t2 on t1.currency = t2.symbol and t1.metadata ->> 'chain' = t2.chain \
             where user_address = $1 and id < $2 order by id desc limit $3",
             user_wallet,
@@ -54,8 +56,8 @@ impl TransactionDao {
         }
         let query = query!(
             "INSERT INTO user_transactions (user_address, transaction_id, from_address,\
-                to_address, amount, currency, type, status, metadata) VALUES \
-                ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+                to_address, amount, currency, type, status, metadata, batch_id) VALUES \
+                ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
             txn.user_address.clone(),
             txn.transaction_id.clone(),
             txn.from_address.clone(),
@@ -64,7 +66,8 @@ impl TransactionDao {
             txn.currency.clone(),
             txn.transaction_type.clone(),
             txn.status.clone(),
-            metadata
+            metadata,
+            txn.batch_id.clone()
         );
         let result = query.execute(&self.pool).await;
         if result.is_err() {
@@ -79,18 +82,18 @@ impl TransactionDao {
     pub async fn get_transaction_by_id(
         pool: &Pool<Postgres>,
         txn_id: String,
-        user_wallet_address: String,
+        user_wallet_addresses: Vec<String>,
     ) -> UserTransaction {
         let query = query_as!(
             UserTransaction,
             "SELECT t1.id, t1.user_address, t1.transaction_id, t1.from_address, \
             t1.to_address, t1.amount, t1.currency, t1.type as transaction_type, \
-            t1.status, t1.metadata, t1.created_at, t1.updated_at, t2.exponent \
+            t1.status, t1.metadata, t1.created_at, t1.updated_at, t2.exponent, t1.batch_id \
             from user_transactions t1 left join token_metadata t2 \
             on t1.currency = t2.symbol and t1.metadata ->> 'chain' = t2.chain \
-            where transaction_id = $1 and user_address = $2",
+            where transaction_id = $1 and user_address = ANY($2)",
             txn_id,
-            user_wallet_address,
+            &user_wallet_addresses[..],
         );
         let result = query.fetch_one(pool).await;
         return match result {
@@ -102,14 +105,48 @@ impl TransactionDao {
         };
     }
 
-    pub async fn update_user_transaction(&self, txn_id: String, txn_hash: String, status: String) {
+    // A batch pays for its user operation once, so the receipt is split across its legs
+    // rather than written in full to each of them.
+    pub async fn update_user_transaction(
+        &self,
+        txn_id: String,
+        receipt: TransactionReceiptMetadata,
+        status: String,
+    ) {
+        let legs = query!(
+            "SELECT id from user_transactions where transaction_id = $1 or batch_id = $1 \
+            order by id",
+            txn_id,
+        )
+        .fetch_all(&self.pool)
+        .await;
+        let legs: Vec<i32> = match legs {
+            Ok(legs) => legs.into_iter().map(|leg| leg.id).collect(),
+            Err(err) => {
+                error!(
+                    "Failed to fetch user transaction: {}, err: {:?}",
+                    txn_id, err
+                );
+                return;
+            }
+        };
+        let mut receipts = vec![];
+        for leg_receipt in receipt.split(legs.len()) {
+            match serde_json::to_value(&leg_receipt) {
+                Ok(data) => receipts.push(data),
+                Err(err) => {
+                    error!("Receipt conversion failed: {}, err: {:?}", txn_id, err);
+                    return;
+                }
+            }
+        }
         let query = query!(
-            "UPDATE user_transactions \
-            set status = $1, metadata = jsonb_set(metadata, '{transaction_hash}', $2) \
-            where transaction_id = $3",
+            "UPDATE user_transactions t \
+            set status = $1, metadata = t.metadata || legs.receipt, updated_at = now() \
+            from unnest($2::int[], $3::jsonb[]) as legs(id, receipt) where t.id = legs.id",
             status,
-            Value::String(txn_hash),
-            txn_id,
+            &legs[..],
+            &receipts[..],
         );
         let result = query.execute(&self.pool).await;
         if result.is_err() {
@@ -120,6 +157,27 @@ impl TransactionDao {
             );
         }
     }
+
+    // the legs of a batch transfer are updated through the batch id they share
+    pub async fn update_user_transactions_status(&self, txn_ids: Vec<String>, status: String) {
+        if txn_ids.is_empty() {
+            return;
+        }
+        let query = query!(
+            "UPDATE user_transactions set status = $1, updated_at = now() \
+            where transaction_id = ANY($2) or batch_id = ANY($2)",
+            status,
+            &txn_ids[..],
+        );
+        let result = query.execute(&self.pool).await;
+        if result.is_err() {
+            error!(
+                "Failed to update user transactions: {:?}, err: {:?}",
+                txn_ids,
+                result.err()
+            );
+        }
+    }
 }
 
 #[derive(Clone, Default)]
@@ -137,6 +195,7 @@ pub struct UserTransaction {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub exponent: i32,
+    pub batch_id: Option<String>,
 }
 
 #[derive(Default, Serialize, Deserialize, Clone)]
@@ -147,12 +206,85 @@ pub struct TransactionMetadata {
     pub gas: Gas,
     pub from_name: String,
     pub transaction_hash: String,
+    #[serde(default)]
+    pub gas_used: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
 }
 
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct TransactionReceiptMetadata {
+    pub transaction_hash: String,
+    pub gas: Gas,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_erc20: Option<Gas>,
+    pub gas_used: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
+}
+
+// Gas costs in wei or token units can exceed a u64, so they are stored as decimal strings.
+// Receipts recorded as plain numbers before that still read back.
 #[derive(Default, Serialize, Deserialize, Clone)]
 pub struct Gas {
     pub currency: String,
-    pub value: u64,
+    #[serde(with = "gas_value")]
+    pub value: U256,
+}
+
+mod gas_value {
+    use ethers::types::U256;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum GasValue {
+        Number(u64),
+        String(String),
+    }
+
+    pub fn serialize<S>(value: &U256, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<U256, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match GasValue::deserialize(deserializer)? {
+            GasValue::Number(value) => Ok(U256::from(value)),
+            GasValue::String(value) => U256::from_dec_str(&value)
+                .map_err(|err| D::Error::custom(format!("invalid gas value {}: {}", value, err))),
+        }
+    }
+}
+
+impl TransactionReceiptMetadata {
+    // splits gas evenly across `legs` receipts, the first legs taking the remainder
+    pub fn split(&self, legs: usize) -> Vec<TransactionReceiptMetadata> {
+        let legs = legs as u64;
+        let share = |total: U256, leg: u64| {
+            total / legs + U256::from(u64::from(U256::from(leg) < total % legs))
+        };
+        (0..legs)
+            .map(|leg| TransactionReceiptMetadata {
+                gas: Gas {
+                    currency: self.gas.currency.clone(),
+                    value: share(self.gas.value, leg),
+                },
+                gas_erc20: self.gas_erc20.as_ref().map(|gas_erc20| Gas {
+                    currency: gas_erc20.currency.clone(),
+                    value: share(gas_erc20.value, leg),
+                }),
+                gas_used: share(U256::from(self.gas_used), leg).as_u64(),
+                ..self.clone()
+            })
+            .collect()
+    }
 }
 
 impl TransactionMetadata {
@@ -165,8 +297,8 @@ impl TransactionMetadata {
         self
     }
 
-    pub fn transaction_hash(&mut self, transaction_hash: String) -> &mut TransactionMetadata {
-        self.transaction_hash = transaction_hash;
+    pub fn gas_erc20(&mut self, gas_erc20: Gas) -> &mut TransactionMetadata {
+        self.gas_erc20 = gas_erc20;
         self
     }
 }
@@ -226,4 +358,80 @@ impl UserTransaction {
         self.metadata = metadata;
         self
     }
+
+    pub fn batch_id(&mut self, batch_id: Option<String>) -> &mut UserTransaction {
+        self.batch_id = batch_id;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::U256;
+    use serde_json::json;
+
+    use super::{Gas, TransactionReceiptMetadata};
+
+    fn receipt(gas: u64, gas_erc20: Option<u64>, gas_used: u64) -> TransactionReceiptMetadata {
+        TransactionReceiptMetadata {
+            transaction_hash: String::from("0x01"),
+            gas: Gas {
+                currency: String::from("ETH"),
+                value: U256::from(gas),
+            },
+            gas_erc20: gas_erc20.map(|value| Gas {
+                currency: String::from("USDC"),
+                value: U256::from(value),
+            }),
+            gas_used,
+            revert_reason: None,
+        }
+    }
+
+    #[test]
+    fn single_leg_keeps_the_receipt() {
+        let legs = receipt(1000, Some(7), 21000).split(1);
+        assert_eq!(legs.len(), 1);
+        assert_eq!(legs[0].gas.value, U256::from(1000));
+        assert_eq!(legs[0].gas_erc20.as_ref().unwrap().value, U256::from(7));
+        assert_eq!(legs[0].gas_used, 21000);
+    }
+
+    #[test]
+    fn legs_add_up_to_the_receipt() {
+        let legs = receipt(1000, Some(7), 21001).split(3);
+        let gas: Vec<u64> = legs.iter().map(|leg| leg.gas.value.as_u64()).collect();
+        let gas_erc20: Vec<u64> = legs
+            .iter()
+            .map(|leg| leg.gas_erc20.as_ref().unwrap().value.as_u64())
+            .collect();
+        let gas_used: Vec<u64> = legs.iter().map(|leg| leg.gas_used).collect();
+        assert_eq!(gas, vec![334, 333, 333]);
+        assert_eq!(gas_erc20, vec![3, 2, 2]);
+        assert_eq!(gas_used, vec![7001, 7000, 7000]);
+        assert!(legs.iter().all(|leg| leg.transaction_hash == "0x01"));
+    }
+
+    #[test]
+    fn no_legs_no_receipts() {
+        assert!(receipt(1000, None, 21000).split(0).is_empty());
+    }
+
+    #[test]
+    fn gas_values_above_u64_round_trip() {
+        let gas = Gas {
+            currency: String::from("ETH"),
+            value: U256::from(u64::MAX) * 10,
+        };
+        let json = serde_json::to_value(&gas).unwrap();
+        assert_eq!(json["value"], json!("184467440737095516150"));
+        let gas: Gas = serde_json::from_value(json).unwrap();
+        assert_eq!(gas.value, U256::from(u64::MAX) * 10);
+    }
+
+    #[test]
+    fn reads_gas_values_stored_as_numbers() {
+        let gas: Gas = serde_json::from_value(json!({"currency": "ETH", "value": 21000})).unwrap();
+        assert_eq!(gas.value, U256::from(21000));
+    }
 }
@@ -0,0 +1,289 @@
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use log::error;
+use serde_json::Value;
+use sqlx::{query, query_as, Pool, Postgres};
+
+use crate::models::contract_interaction::user_operation::UserOperation;
+use crate::models::transfer::status::Status;
+
+#[derive(Clone)]
+pub struct UserOperationDao {
+    pub pool: Pool<Postgres>,
+}
+
+impl UserOperationDao {
+    pub async fn create_user_operation(
+        &self,
+        user_op_hash: String,
+        user_op: &UserOperation,
+        transaction_id: Option<String>,
+        status: String,
+        chain: String,
+    ) -> Result<(), String> {
+        let user_operation: Value = match serde_json::to_value(user_op) {
+            Ok(data) => data,
+            Err(err) => {
+                error!(
+                    "User operation conversion failed: {}, err: {:?}",
+                    user_op_hash, err
+                );
+                return Err(String::from("failed to save user operation"));
+            }
+        };
+        let query = query!(
+            "INSERT INTO user_operations (user_op_hash, sender, nonce, user_operation, \
+            transaction_id, status, chain) VALUES ($1, $2, $3, $4, $5, $6, $7) \
+            on conflict (user_op_hash) do update set user_operation = $4, transaction_id = $5, \
+            status = $6, chain = $7, updated_at = now()",
+            user_op_hash,
+            format!("{:?}", user_op.sender),
+            BigDecimal::from(user_op.nonce),
+            user_operation,
+            transaction_id,
+            status,
+            chain
+        );
+        let result = query.execute(&self.pool).await;
+        if result.is_err() {
+            error!(
+                "Failed to create user operation: {}, err: {:?}",
+                user_op_hash,
+                result.err()
+            );
+            return Err(String::from("failed to save user operation"));
+        }
+        Ok(())
+    }
+
+    pub async fn get_user_operations_by_status(
+        &self,
+        status: String,
+        chain: String,
+    ) -> Vec<UserOperationRecord> {
+        let query = query_as!(
+            UserOperationRecord,
+            "SELECT user_op_hash, user_operation, transaction_id FROM user_operations \
+            WHERE status = $1 and chain = $2 order by created_at",
+            status,
+            chain
+        );
+        let result = query.fetch_all(&self.pool).await;
+        match result {
+            Ok(rows) => rows,
+            Err(err) => {
+                error!("Failed to fetch user operations: {:?}", err);
+                vec![]
+            }
+        }
+    }
+
+    pub async fn get_submitted_user_operations(
+        &self,
+        chain: String,
+    ) -> Vec<SubmittedUserOperation> {
+        let query = query_as!(
+            SubmittedUserOperation,
+            "SELECT user_op_hash, transaction_id, transaction_hash as \"transaction_hash!\", \
+            submitted_block as \"submitted_block!\", submitted_at as \"submitted_at!\" \
+            FROM user_operations WHERE status = $1 and chain = $2 and transaction_hash is not null \
+            and submitted_block is not null and submitted_at is not null order by submitted_at",
+            Status::SUBMITTED.to_string(),
+            chain
+        );
+        let result = query.fetch_all(&self.pool).await;
+        match result {
+            Ok(rows) => rows,
+            Err(err) => {
+                error!("Failed to fetch submitted user operations: {:?}", err);
+                vec![]
+            }
+        }
+    }
+
+    pub async fn submit_user_operations(
+        &self,
+        user_op_hashes: Vec<String>,
+        transaction_hash: String,
+        submitted_block: u64,
+    ) {
+        let query = query!(
+            "UPDATE user_operations set status = $1, transaction_hash = $2, submitted_block = $3, \
+            submitted_at = now(), updated_at = now() where user_op_hash = ANY($4)",
+            Status::SUBMITTED.to_string(),
+            transaction_hash,
+            submitted_block as i64,
+            &user_op_hashes[..],
+        );
+        let result = query.execute(&self.pool).await;
+        if result.is_err() {
+            error!(
+                "Failed to submit user operations: {:?}, err: {:?}",
+                user_op_hashes,
+                result.err()
+            );
+        }
+    }
+
+    pub async fn get_included_user_operations(&self, chain: String) -> Vec<IncludedUserOperation> {
+        let query = query_as!(
+            IncludedUserOperation,
+            "SELECT user_op_hash, sender, transaction_id, \
+            transaction_hash as \"transaction_hash!\", \
+            block_number as \"block_number!\", block_hash as \"block_hash!\", \
+            success as \"success!\" FROM user_operations WHERE status = $1 and chain = $2 \
+            and transaction_hash is not null and block_number is not null \
+            and block_hash is not null and success is not null order by block_number",
+            Status::INCLUDED.to_string(),
+            chain
+        );
+        let result = query.fetch_all(&self.pool).await;
+        match result {
+            Ok(rows) => rows,
+            Err(err) => {
+                error!("Failed to fetch included user operations: {:?}", err);
+                vec![]
+            }
+        }
+    }
+
+    pub async fn include_user_operation(
+        &self,
+        user_op_hash: String,
+        transaction_hash: String,
+        block_number: u64,
+        block_hash: String,
+        success: bool,
+    ) {
+        let query = query!(
+            "UPDATE user_operations set status = $1, transaction_hash = $2, block_number = $3, \
+            block_hash = $4, success = $5, updated_at = now() where user_op_hash = $6",
+            Status::INCLUDED.to_string(),
+            transaction_hash,
+            block_number as i64,
+            block_hash,
+            success,
+            user_op_hash,
+        );
+        let result = query.execute(&self.pool).await;
+        if result.is_err() {
+            error!(
+                "Failed to include user operation: {}, err: {:?}",
+                user_op_hash,
+                result.err()
+            );
+        }
+    }
+
+    // a resubmitted operation is tracked again from its original block with a fresh deadline
+    pub async fn revert_user_operation_inclusion(&self, user_op_hash: String, status: String) {
+        let query = query!(
+            "UPDATE user_operations set status = $1, block_number = null, block_hash = null, \
+            success = null, submitted_at = now(), updated_at = now() where user_op_hash = $2",
+            status,
+            user_op_hash,
+        );
+        let result = query.execute(&self.pool).await;
+        if result.is_err() {
+            error!(
+                "Failed to revert user operation inclusion: {}, err: {:?}",
+                user_op_hash,
+                result.err()
+            );
+        }
+    }
+
+    // A replaced operation is dropped together with the user transactions it carried, unless
+    // those are carried on by the replacement.
+    pub async fn drop_replaced_user_operation(
+        &self,
+        user_op_hash: String,
+        transaction_id: Option<String>,
+    ) {
+        let query = query!(
+            "WITH replaced AS (UPDATE user_operations set status = $1, updated_at = now() \
+            where user_op_hash = $2 returning transaction_id) \
+            UPDATE user_transactions set status = $1, updated_at = now() \
+            from replaced where replaced.transaction_id is distinct from $3 \
+            and (user_transactions.transaction_id = replaced.transaction_id \
+            or user_transactions.batch_id = replaced.transaction_id)",
+            Status::DROPPED.to_string(),
+            user_op_hash,
+            transaction_id,
+        );
+        let result = query.execute(&self.pool).await;
+        if result.is_err() {
+            error!(
+                "Failed to drop replaced user operation: {}, err: {:?}",
+                user_op_hash,
+                result.err()
+            );
+        }
+    }
+
+    // operations queued before chains were recorded all belong to the default chain
+    pub async fn assign_chain(&self, chain: String) {
+        let query = query!(
+            "UPDATE user_operations set chain = $1 where chain is null",
+            chain
+        );
+        let result = query.execute(&self.pool).await;
+        if result.is_err() {
+            error!(
+                "Failed to assign chain to user operations: {}, err: {:?}",
+                chain,
+                result.err()
+            );
+        }
+    }
+
+    pub async fn update_user_operations(
+        &self,
+        user_op_hashes: Vec<String>,
+        transaction_hash: Option<String>,
+        status: String,
+    ) {
+        let query = query!(
+            "UPDATE user_operations set status = $1, transaction_hash = $2, updated_at = now() \
+            where user_op_hash = ANY($3)",
+            status,
+            transaction_hash,
+            &user_op_hashes[..],
+        );
+        let result = query.execute(&self.pool).await;
+        if result.is_err() {
+            error!(
+                "Failed to update user operations: {:?}, err: {:?}",
+                user_op_hashes,
+                result.err()
+            );
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SubmittedUserOperation {
+    pub user_op_hash: String,
+    pub transaction_id: Option<String>,
+    pub transaction_hash: String,
+    pub submitted_block: i64,
+    pub submitted_at: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct IncludedUserOperation {
+    pub user_op_hash: String,
+    pub sender: String,
+    pub transaction_id: Option<String>,
+    pub transaction_hash: String,
+    pub block_number: i64,
+    pub block_hash: String,
+    pub success: bool,
+}
+
+#[derive(Clone)]
+pub struct UserOperationRecord {
+    pub user_op_hash: String,
+    pub user_operation: Value,
+    pub transaction_id: Option<String>,
+}
@@ -1,6 +1,6 @@
 use bigdecimal::BigDecimal;
 use log::error;
-use sqlx::{query, query_as, Error, Pool, Postgres};
+use sqlx::{query, query_as, query_scalar, Error, Pool, Postgres};
 
 #[derive(Clone)]
 pub struct WalletDao {
@@ -8,40 +8,63 @@ pub struct WalletDao {
 }
 
 impl WalletDao {
-    pub async fn update_wallet_deployed(&self, user_id: String) {
+    pub async fn update_wallet_deployed(&self, wallet_address: String, chain: String) {
         let query = query!(
-            "UPDATE users SET deployed = $1 WHERE email = $2",
+            "UPDATE users SET deployed = $1 WHERE wallet_address = $2 and chain = $3 \
+            and deployed = false",
             true,
-            user_id
+            wallet_address.to_lowercase(),
+            chain
         );
         let result = query.execute(&self.pool).await;
         if result.is_err() {
             error!(
-                "Failed to update deployed status for user: {}, err: {:?}",
-                user_id,
+                "Failed to update deployed status for wallet: {}, err: {:?}",
+                wallet_address,
                 result.err()
             );
         }
     }
 
-    pub async fn get_wallet_address(&self, user_id: String) -> String {
-        Self::get_user_wallet_address(&self.pool, user_id).await
-    }
-
-    pub async fn get_user_wallet_address(pool: &Pool<Postgres>, user_id: String) -> String {
-        let query = query_as!(User, "SELECT * from users where email = $1", user_id);
-        let result: Result<User, Error> = query.fetch_one(pool).await;
+    pub async fn get_wallet_address(&self, user_id: String, chain: String) -> String {
+        let query = query_as!(
+            User,
+            "SELECT * from users where email = $1 and chain = $2",
+            user_id,
+            chain
+        );
+        let result: Result<User, Error> = query.fetch_one(&self.pool).await;
         return match result {
             Ok(user) => user.wallet_address,
             Err(err) => {
-                error!("Failed to get wallet address {}, err: {:?}", user_id, err);
+                error!(
+                    "Failed to get wallet address {} on {}, err: {:?}",
+                    user_id, chain, err
+                );
                 "".to_string()
             }
         };
     }
 
-    pub async fn get_wallet(&self, user_id: String) -> Option<User> {
+    pub async fn get_user_wallet_addresses(pool: &Pool<Postgres>, user_id: String) -> Vec<String> {
         let query = query_as!(User, "SELECT * from users where email = $1", user_id);
+        let result: Result<Vec<User>, Error> = query.fetch_all(pool).await;
+        match result {
+            Ok(users) => users.into_iter().map(|user| user.wallet_address).collect(),
+            Err(err) => {
+                error!("Failed to get wallet addresses {}, err: {:?}", user_id, err);
+                vec![]
+            }
+        }
+    }
+
+    pub async fn get_wallet(&self, user_id: String, chain: String) -> Option<User> {
+        let query = query_as!(
+            User,
+            "SELECT * from users where email = $1 and chain = $2",
+            user_id,
+            chain
+        );
         let result: Result<Option<User>, Error> = query.fetch_optional(&self.pool).await;
         return match result {
             Ok(user) => user,
@@ -52,29 +75,77 @@ impl WalletDao {
         };
     }
 
-    pub async fn create_wallet(
-        &self,
-        user_id: String,
-        wallet_address: String,
-        salt: BigDecimal,
-        deployed: bool,
-    ) {
-        let query = query!(
-            "INSERT INTO users (email, wallet_address, salt, deployed) VALUES ($1, $2, $3, $4)",
-            user_id,
-            wallet_address.to_lowercase(),
-            salt,
-            deployed
-        );
+    pub async fn get_wallets(&self) -> Vec<User> {
+        let query = query_as!(User, "SELECT * from users");
+        let result: Result<Vec<User>, Error> = query.fetch_all(&self.pool).await;
+        match result {
+            Ok(users) => users,
+            Err(err) => {
+                error!("Failed to get wallets, err: {:?}", err);
+                vec![]
+            }
+        }
+    }
+
+    // wallets created before chains were recorded all live on the default chain
+    pub async fn assign_chain(&self, chain: String) {
+        let query = query!("UPDATE users SET chain = $1 WHERE chain is null", chain);
         let result = query.execute(&self.pool).await;
         if result.is_err() {
             error!(
-                "Failed to create user: {}, err: {:?}",
-                user_id,
+                "Failed to assign chain to wallets: {}, err: {:?}",
+                chain,
                 result.err()
             );
         }
     }
+
+    // Concurrent first calls for a user race to insert; the loser gets the stored wallet back,
+    // never the one it generated, since only the stored wallet has its owner key saved.
+    pub async fn create_wallet(&self, wallet: NewWallet) -> Result<String, String> {
+        let query = query_scalar!(
+            "INSERT INTO users (email, wallet_address, salt, deployed, owner_address, \
+            encrypted_owner_key, salt_scheme_version, chain) \
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8) \
+            ON CONFLICT (email, chain) DO NOTHING RETURNING wallet_address",
+            wallet.user_id,
+            wallet.wallet_address.to_lowercase(),
+            wallet.salt,
+            wallet.deployed,
+            wallet.owner_address.to_lowercase(),
+            wallet.encrypted_owner_key,
+            wallet.salt_scheme_version,
+            wallet.chain
+        );
+        let inserted = query.fetch_optional(&self.pool).await.map_err(|err| {
+            error!("Failed to create user: {}, err: {:?}", wallet.user_id, err);
+            String::from("Failed to create wallet")
+        })?;
+        if let Some(wallet_address) = inserted {
+            return Ok(wallet_address);
+        }
+        let query = query_scalar!(
+            "SELECT wallet_address from users where email = $1 and chain = $2",
+            wallet.user_id,
+            wallet.chain
+        );
+        query.fetch_one(&self.pool).await.map_err(|err| {
+            error!("Failed to get user: {}, err: {:?}", wallet.user_id, err);
+            String::from("Failed to create wallet")
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct NewWallet {
+    pub user_id: String,
+    pub wallet_address: String,
+    pub salt: BigDecimal,
+    pub deployed: bool,
+    pub owner_address: String,
+    pub encrypted_owner_key: String,
+    pub salt_scheme_version: i32,
+    pub chain: String,
 }
 
 #[derive(Clone, Debug)]
@@ -83,4 +154,8 @@ pub struct User {
     pub wallet_address: String,
     pub salt: BigDecimal,
     pub deployed: bool,
+    pub owner_address: Option<String>,
+    pub encrypted_owner_key: Option<String>,
+    pub salt_scheme_version: i32,
+    pub chain: Option<String>,
 }
@@ -1,20 +1,33 @@
+use actix_web::http::header::RETRY_AFTER;
 use actix_web::{HttpResponse, ResponseError};
 use derive_more::Display;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
+use crate::models::contract_interaction::user_operation_error::UserOperationError;
+
 #[derive(Debug, Display)]
 #[allow(dead_code)]
 pub enum ApiError {
     BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
     NotFound(String),
+    Conflict(String),
     InternalServer(String),
+    UserOperationRejected(UserOperationError),
+    SponsorshipDenied(String),
+    // the number of seconds until the limit resets goes out as Retry-After
+    #[display(fmt = "{}", _0)]
+    TooManyRequests(String, u64),
 }
 
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Error {
     #[serde(skip_serializing_if = "String::is_empty")]
     message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -29,12 +42,30 @@ impl ResponseError for ApiError {
             ApiError::BadRequest(error) => {
                 HttpResponse::BadRequest().json(ErrorResponse::from(String::from(error)))
             }
+            ApiError::Unauthorized(message) => {
+                HttpResponse::Unauthorized().json(ErrorResponse::from(String::from(message)))
+            }
+            ApiError::Forbidden(message) => {
+                HttpResponse::Forbidden().json(ErrorResponse::from(String::from(message)))
+            }
             ApiError::NotFound(message) => {
                 HttpResponse::NotFound().json(ErrorResponse::from(String::from(message)))
             }
+            ApiError::Conflict(message) => {
+                HttpResponse::Conflict().json(ErrorResponse::from(String::from(message)))
+            }
             ApiError::InternalServer(message) => {
                 HttpResponse::InternalServerError().json(ErrorResponse::from(String::from(message)))
             }
+            ApiError::UserOperationRejected(error) => {
+                HttpResponse::BadRequest().json(ErrorResponse::from(error.clone()))
+            }
+            ApiError::SponsorshipDenied(reason) => HttpResponse::Forbidden().json(
+                ErrorResponse::from(format!("Sponsorship denied: {}", reason)),
+            ),
+            ApiError::TooManyRequests(message, retry_after) => HttpResponse::TooManyRequests()
+                .insert_header((RETRY_AFTER, retry_after.to_string()))
+                .json(ErrorResponse::from(String::from(message))),
         }
     }
 }
@@ -43,7 +74,22 @@ impl From<String> for ErrorResponse {
     fn from(error: String) -> Self {
         ErrorResponse {
             data: json!({}),
-            err: Error { message: error },
+            err: Error {
+                message: error,
+                code: None,
+            },
+        }
+    }
+}
+
+impl From<UserOperationError> for ErrorResponse {
+    fn from(error: UserOperationError) -> Self {
+        ErrorResponse {
+            data: json!({}),
+            err: Error {
+                code: error.entrypoint_code(),
+                message: error.reason,
+            },
         }
     }
 }
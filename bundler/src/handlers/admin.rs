@@ -1,61 +1,254 @@
-use crate::CONFIG;
 use actix_web::web::{Data, Json, Path, Query};
-use actix_web::HttpRequest;
+use serde_json::json;
 
 use crate::errors::ApiError;
 use crate::models::admin::add_metadata_request::AddMetadataRequest;
+use crate::models::admin::audit_log_params::AuditLogParams;
+use crate::models::admin::audit_log_response::AuditLogResponse;
+use crate::models::admin::call_target_request::{CallTargetRequest, CallTargetsRequest};
+use crate::models::admin::call_targets_response::CallTargetsResponse;
+use crate::models::admin::deposit_info_response::DepositInfoResponse;
 use crate::models::admin::metadata_response::MetadataResponse;
+use crate::models::admin::paymaster_request::{
+    PaymasterRequest, PaymasterStakeRequest, PaymasterWithdrawRequest,
+};
 use crate::models::admin::paymaster_topup::PaymasterTopup;
+use crate::models::admin::role::Permission;
+use crate::models::principal::Principal;
 use crate::models::response::base_response::BaseResponse;
 use crate::models::transfer::transfer_response::TransferResponse;
 use crate::models::wallet::balance_request::BalanceRequest;
 use crate::models::wallet::balance_response::BalanceResponse;
-use crate::provider::helpers::{get_user, respond_json};
+use crate::provider::helpers::respond_json;
 use crate::services::admin_service::AdminService;
 
 pub async fn topup_paymaster_deposit(
     service: Data<AdminService>,
     body: Json<PaymasterTopup>,
-    req: HttpRequest,
+    principal: Principal,
     paymaster: Path<String>,
 ) -> Result<Json<BaseResponse<TransferResponse>>, ApiError> {
-    if is_not_admin(get_user(req)) {
-        return Err(ApiError::BadRequest("Invalid credentials".to_string()));
-    }
     let req = body.into_inner();
+    let action = "topup_paymaster_deposit";
+    let parameters = json!({"paymaster": paymaster.as_str(), "request": &req});
+    service
+        .authorize(&principal, Permission::ManageFunds, action, &parameters)
+        .await?;
     let response = service
         .topup_paymaster_deposit(req.value, paymaster.clone(), req.metadata)
+        .await;
+    service
+        .record_action(&principal, action, parameters, &response)
+        .await;
+    respond_json(response?)
+}
+
+pub async fn stake_paymaster(
+    service: Data<AdminService>,
+    body: Json<PaymasterStakeRequest>,
+    principal: Principal,
+    paymaster: Path<String>,
+) -> Result<Json<BaseResponse<TransferResponse>>, ApiError> {
+    let req = body.into_inner();
+    let action = "stake_paymaster";
+    let parameters = json!({"paymaster": paymaster.as_str(), "request": &req});
+    service
+        .authorize(&principal, Permission::ManageFunds, action, &parameters)
+        .await?;
+    let response = service.stake_paymaster(paymaster.clone(), req).await;
+    service
+        .record_action(&principal, action, parameters, &response)
+        .await;
+    respond_json(response?)
+}
+
+pub async fn unlock_paymaster_stake(
+    service: Data<AdminService>,
+    body: Json<PaymasterRequest>,
+    principal: Principal,
+    paymaster: Path<String>,
+) -> Result<Json<BaseResponse<TransferResponse>>, ApiError> {
+    let req = body.into_inner();
+    let action = "unlock_paymaster_stake";
+    let parameters = json!({"paymaster": paymaster.as_str(), "request": &req});
+    service
+        .authorize(&principal, Permission::ManageFunds, action, &parameters)
+        .await?;
+    let response = service.unlock_paymaster_stake(paymaster.clone(), req).await;
+    service
+        .record_action(&principal, action, parameters, &response)
+        .await;
+    respond_json(response?)
+}
+
+pub async fn withdraw_paymaster_stake(
+    service: Data<AdminService>,
+    body: Json<PaymasterWithdrawRequest>,
+    principal: Principal,
+    paymaster: Path<String>,
+) -> Result<Json<BaseResponse<TransferResponse>>, ApiError> {
+    let req = body.into_inner();
+    let action = "withdraw_paymaster_stake";
+    let parameters = json!({"paymaster": paymaster.as_str(), "request": &req});
+    service
+        .authorize(&principal, Permission::ManageFunds, action, &parameters)
+        .await?;
+    let response = service
+        .withdraw_paymaster_stake(paymaster.clone(), req)
+        .await;
+    service
+        .record_action(&principal, action, parameters, &response)
+        .await;
+    respond_json(response?)
+}
+
+pub async fn withdraw_paymaster_deposit(
+    service: Data<AdminService>,
+    body: Json<PaymasterWithdrawRequest>,
+    principal: Principal,
+    paymaster: Path<String>,
+) -> Result<Json<BaseResponse<TransferResponse>>, ApiError> {
+    let req = body.into_inner();
+    let action = "withdraw_paymaster_deposit";
+    let parameters = json!({"paymaster": paymaster.as_str(), "request": &req});
+    service
+        .authorize(&principal, Permission::ManageFunds, action, &parameters)
+        .await?;
+    let response = service
+        .withdraw_paymaster_deposit(paymaster.clone(), req)
+        .await;
+    service
+        .record_action(&principal, action, parameters, &response)
+        .await;
+    respond_json(response?)
+}
+
+pub async fn get_paymaster_deposit_info(
+    service: Data<AdminService>,
+    body: Query<PaymasterRequest>,
+    principal: Principal,
+    paymaster: Path<String>,
+) -> Result<Json<BaseResponse<DepositInfoResponse>>, ApiError> {
+    let req = body.into_inner();
+    let action = "get_paymaster_deposit_info";
+    let parameters = json!({"paymaster": paymaster.as_str(), "request": &req});
+    service
+        .authorize(&principal, Permission::Read, action, &parameters)
         .await?;
-    respond_json(response)
+    let response = service
+        .get_paymaster_deposit_info(paymaster.clone(), req)
+        .await;
+    service
+        .record_action(&principal, action, parameters, &response)
+        .await;
+    respond_json(response?)
 }
 
 pub async fn admin_get_balance(
     service: Data<AdminService>,
     body: Query<BalanceRequest>,
-    req: HttpRequest,
+    principal: Principal,
     entity: Path<String>,
 ) -> Result<Json<BaseResponse<BalanceResponse>>, ApiError> {
-    if is_not_admin(get_user(req)) {
-        return Err(ApiError::BadRequest("Invalid credentials".to_string()));
-    }
-    let response = service
-        .get_balance(entity.clone(), body.get_balance_request())
+    let req = body.get_balance_request();
+    let action = "get_balance";
+    let parameters = json!({"entity": entity.as_str(), "request": &req});
+    service
+        .authorize(&principal, Permission::Read, action, &parameters)
         .await?;
-    respond_json(response)
+    let response = service.get_balance(entity.clone(), req).await;
+    service
+        .record_action(&principal, action, parameters, &response)
+        .await;
+    respond_json(response?)
 }
 
 pub async fn add_currency_metadata(
     service: Data<AdminService>,
     body: Json<AddMetadataRequest>,
-    req: HttpRequest,
+    principal: Principal,
 ) -> Result<Json<BaseResponse<MetadataResponse>>, ApiError> {
-    if is_not_admin(get_user(req)) {
-        return Err(ApiError::BadRequest("Invalid credentials".to_string()));
-    }
-    let response = service.add_currency_metadata(body.into_inner()).await?;
-    respond_json(response)
+    let req = body.into_inner();
+    let action = "add_currency_metadata";
+    let parameters = json!(&req);
+    service
+        .authorize(&principal, Permission::ManageTokens, action, &parameters)
+        .await?;
+    let response = service.add_currency_metadata(req).await;
+    service
+        .record_action(&principal, action, parameters, &response)
+        .await;
+    respond_json(response?)
+}
+
+pub async fn add_call_target(
+    service: Data<AdminService>,
+    body: Json<CallTargetRequest>,
+    principal: Principal,
+) -> Result<Json<BaseResponse<CallTargetsResponse>>, ApiError> {
+    let req = body.into_inner();
+    let action = "add_call_target";
+    let parameters = json!(&req);
+    service
+        .authorize(&principal, Permission::ManageTokens, action, &parameters)
+        .await?;
+    let response = service.add_call_target(req).await;
+    service
+        .record_action(&principal, action, parameters, &response)
+        .await;
+    respond_json(response?)
+}
+
+pub async fn remove_call_target(
+    service: Data<AdminService>,
+    body: Json<CallTargetRequest>,
+    principal: Principal,
+) -> Result<Json<BaseResponse<CallTargetsResponse>>, ApiError> {
+    let req = body.into_inner();
+    let action = "remove_call_target";
+    let parameters = json!(&req);
+    service
+        .authorize(&principal, Permission::ManageTokens, action, &parameters)
+        .await?;
+    let response = service.remove_call_target(req).await;
+    service
+        .record_action(&principal, action, parameters, &response)
+        .await;
+    respond_json(response?)
 }
 
-fn is_not_admin(user: String) -> bool {
-    !CONFIG.get_admins().contains(&user)
+pub async fn get_call_targets(
+    service: Data<AdminService>,
+    query: Query<CallTargetsRequest>,
+    principal: Principal,
+) -> Result<Json<BaseResponse<CallTargetsResponse>>, ApiError> {
+    let req = query.into_inner();
+    let action = "get_call_targets";
+    let parameters = json!(&req);
+    service
+        .authorize(&principal, Permission::Read, action, &parameters)
+        .await?;
+    let response = service.get_call_targets(req.chain.to_lowercase()).await;
+    service
+        .record_action(&principal, action, parameters, &response)
+        .await;
+    respond_json(response?)
+}
+
+pub async fn get_audit_log(
+    service: Data<AdminService>,
+    query: Query<AuditLogParams>,
+    principal: Principal,
+) -> Result<Json<BaseResponse<Vec<AuditLogResponse>>>, ApiError> {
+    let req = query.into_inner();
+    let action = "get_audit_log";
+    let parameters = json!(&req);
+    service
+        .authorize(&principal, Permission::ReadAuditLog, action, &parameters)
+        .await?;
+    let response = service.get_audit_log(req).await;
+    service
+        .record_action(&principal, action, parameters, &response)
+        .await;
+    respond_json(response?)
 }
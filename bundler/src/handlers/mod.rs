@@ -1,4 +1,5 @@
 pub mod admin;
 pub mod hello_world;
 pub mod metadata;
+pub mod rpc;
 pub mod wallet;
@@ -0,0 +1,15 @@
+use actix_web::web::{Data, Json};
+
+use crate::models::principal::Principal;
+use crate::models::rpc::json_rpc_request::JsonRpcRequest;
+use crate::models::rpc::json_rpc_response::JsonRpcResponse;
+use crate::services::rpc_service::RpcService;
+
+// bundler methods are public, paymaster methods need a principal
+pub async fn rpc(
+    service: Data<RpcService>,
+    principal: Option<Principal>,
+    body: Json<JsonRpcRequest>,
+) -> Json<JsonRpcResponse> {
+    Json(service.handle(body.into_inner(), principal.as_ref()).await)
+}
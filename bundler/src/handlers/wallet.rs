@@ -1,42 +1,49 @@
-use actix_web::error::ErrorUnauthorized;
 use actix_web::web::{Data, Json, Query};
 use actix_web::{Error, HttpRequest, HttpResponse};
 use sqlx::{Pool, Postgres};
 
 use crate::errors::ApiError;
+use crate::models::principal::Principal;
 use crate::models::response::base_response::BaseResponse;
 use crate::models::transaction::list_transactions_params::ListTransactionsParams;
 use crate::models::transaction::poll_transaction_params::PollTransactionParams;
 use crate::models::transaction::transaction::Transaction;
+use crate::models::transfer::batch_transfer_request::BatchTransferRequest;
+use crate::models::transfer::batch_transfer_response::BatchTransferResponse;
+use crate::models::transfer::contract_call_request::ContractCallRequest;
 use crate::models::transfer::transfer_request::TransferRequest;
 use crate::models::transfer::transfer_response::TransferResponse;
+use crate::models::wallet::address_request::AddressRequest;
 use crate::models::wallet::address_response::AddressResponse;
 use crate::models::wallet::balance_request::BalanceRequest;
 use crate::models::wallet::balance_response::BalanceResponse;
-use crate::provider::helpers::{get_user, respond_json};
+use crate::provider::helpers::{get_idempotency_key, respond_json};
 use crate::services::balance_service::BalanceService;
 use crate::services::transfer_service::TransferService;
 use crate::services::wallet_service::WalletService;
 
 pub async fn get_address(
     service: Data<WalletService>,
-    req: HttpRequest,
+    query: Query<AddressRequest>,
+    principal: Principal,
 ) -> Result<Json<BaseResponse<AddressResponse>>, ApiError> {
-    let wallet_address = service.get_wallet_address(&get_user(req)).await?;
+    let wallet_address = service
+        .get_wallet_address(&principal.user, &query.get_chain())
+        .await?;
     respond_json(wallet_address)
 }
 
 pub async fn get_balance(
     service: Data<BalanceService>,
     body: Query<BalanceRequest>,
-    req: HttpRequest,
+    principal: Principal,
 ) -> Result<Json<BaseResponse<BalanceResponse>>, ApiError> {
     let balance_request = body.get_balance_request();
     let data = service
         .get_wallet_balance(
             &balance_request.get_chain(),
             &balance_request.get_currency(),
-            &get_user(req),
+            &principal.user,
         )
         .await?;
     respond_json(data)
@@ -45,16 +52,52 @@ pub async fn get_balance(
 pub async fn transfer(
     service: Data<TransferService>,
     body: Json<TransferRequest>,
+    principal: Principal,
     req: HttpRequest,
 ) -> Result<Json<BaseResponse<TransferResponse>>, ApiError> {
     let body = body.into_inner();
+    let data = match get_idempotency_key(&req)? {
+        Some(idempotency_key) => {
+            service
+                .transfer_funds_once(idempotency_key, body, &principal.user)
+                .await?
+        }
+        None => {
+            service
+                .transfer_funds(
+                    body.get_receiver(),
+                    body.get_amount()?,
+                    body.metadata.get_currency(),
+                    body.metadata.get_chain(),
+                    body.get_gas_currency(),
+                    &principal.user,
+                )
+                .await?
+        }
+    };
+    respond_json(data)
+}
+
+pub async fn batch_transfer(
+    service: Data<TransferService>,
+    body: Json<BatchTransferRequest>,
+    principal: Principal,
+) -> Result<Json<BaseResponse<BatchTransferResponse>>, ApiError> {
+    let body = body.into_inner();
+    let chain = body.get_chain();
     let data = service
-        .transfer_funds(
-            body.get_receiver(),
-            body.get_value(),
-            body.metadata.get_currency(),
-            &get_user(req),
-        )
+        .batch_transfer_funds(body.transfers, chain, &principal.user)
+        .await?;
+    respond_json(data)
+}
+
+pub async fn call_contract(
+    service: Data<TransferService>,
+    body: Json<ContractCallRequest>,
+    principal: Principal,
+) -> Result<Json<BaseResponse<TransferResponse>>, ApiError> {
+    let data = service
+        .call_contract(body.into_inner(), &principal.user)
         .await?;
     respond_json(data)
 }
@@ -62,33 +105,32 @@ pub async fn transfer(
 pub async fn list_transactions(
     service: Data<WalletService>,
     query: Query<ListTransactionsParams>,
-    req: HttpRequest,
+    principal: Principal,
 ) -> Result<Json<BaseResponse<Vec<Transaction>>>, ApiError> {
     let query_params = query.into_inner();
     let data = service
         .list_transactions(
             query_params.page_size.unwrap_or(10),
             query_params.id,
-            &get_user(req),
+            &principal.user,
+            &query_params.get_chain(),
         )
-        .await;
+        .await?;
     respond_json(data)
 }
 
 pub async fn poll_transaction(
     db_pool: Data<Pool<Postgres>>,
     query: Query<PollTransactionParams>,
-    req: HttpRequest,
+    principal: Principal,
 ) -> Result<HttpResponse, Error> {
-    let user_id = get_user(req);
-    if user_id.is_empty() {
-        return Err(ErrorUnauthorized(""));
-    }
-
-    let transaction =
-        TransferService::get_status(db_pool.get_ref(), query.transaction_id.clone(), user_id)
-            .await
-            .unwrap();
+    let transaction = TransferService::get_status(
+        db_pool.get_ref(),
+        query.transaction_id.clone(),
+        principal.user,
+    )
+    .await
+    .unwrap();
 
     Ok(HttpResponse::Ok().json(BaseResponse {
         data: transaction,
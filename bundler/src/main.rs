@@ -1,30 +1,44 @@
+use std::collections::HashMap;
+
 use ethers::providers::{Http, Provider};
 use lazy_static::lazy_static;
 
+use crate::commands::verify_wallets::verify_wallets;
 use crate::models::config::server::Server;
 use crate::models::config::settings::Settings;
 use crate::provider::web3_provider::Web3Provider;
 use crate::server::{init_services, run};
 
 mod bundler;
+mod commands;
 mod constants;
 mod contracts;
 mod db;
 mod errors;
 mod handlers;
+mod middleware;
 mod models;
 mod provider;
 mod routes;
 mod server;
 mod services;
+mod signer;
 
 lazy_static! {
     static ref CONFIG: Settings = Settings::new().expect("Failed to load config.");
-    static ref PROVIDER: Provider<Http> = Web3Provider::new(CONFIG.get_chain().get_url());
+    static ref PROVIDERS: HashMap<String, Provider<Http>> = CONFIG
+        .chains
+        .iter()
+        .map(|(name, chain)| (name.clone(), Web3Provider::new(chain.get_url())))
+        .collect();
 }
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
+    if std::env::args().nth(1).as_deref() == Some("verify-wallets") {
+        let verified = verify_wallets().await;
+        std::process::exit(if verified { 0 } else { 1 });
+    }
     let service = init_services().await;
     run(
         service.clone(),
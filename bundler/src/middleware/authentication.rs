@@ -0,0 +1,70 @@
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{Error, HttpMessage, ResponseError};
+
+use crate::provider::authenticator::Authenticator;
+
+type AuthenticationFuture<B> =
+    Pin<Box<dyn Future<Output = Result<ServiceResponse<EitherBody<B>>, Error>>>>;
+
+// Stores the verified principal on the request for the `Principal` extractor. Requests without
+// credentials pass through and are turned away by the handlers that need a principal.
+pub struct Authentication {
+    pub authenticator: Authenticator,
+}
+
+pub struct AuthenticationMiddleware<S> {
+    service: S,
+    authenticator: Authenticator,
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Authentication
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = AuthenticationMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuthenticationMiddleware {
+            service,
+            authenticator: self.authenticator.clone(),
+        }))
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for AuthenticationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = AuthenticationFuture<B>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        match self.authenticator.authenticate(req.request()) {
+            Ok(Some(principal)) => {
+                req.extensions_mut().insert(principal);
+            }
+            Ok(None) => {}
+            Err(err) => {
+                let response = err.error_response();
+                return Box::pin(ready(Ok(req.into_response(response).map_into_right_body())));
+            }
+        }
+        let response = self.service.call(req);
+        Box::pin(async move { response.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
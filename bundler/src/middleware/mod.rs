@@ -0,0 +1,2 @@
+pub mod authentication;
+pub mod rate_limit;
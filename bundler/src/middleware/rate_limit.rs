@@ -0,0 +1,92 @@
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{Error, HttpMessage, ResponseError};
+
+use crate::models::principal::Principal;
+use crate::provider::rate_limiter::RateLimiter;
+use crate::CONFIG;
+
+type RateLimitFuture<B> =
+    Pin<Box<dyn Future<Output = Result<ServiceResponse<EitherBody<B>>, Error>>>>;
+
+// Limits requests per minute for each principal, or each client IP when unauthenticated. Has to
+// run after the authentication middleware to see the principal.
+pub struct RateLimit {
+    pub rate_limiter: RateLimiter,
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: Rc<S>,
+    rate_limiter: RateLimiter,
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RateLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddleware {
+            service: Rc::new(service),
+            rate_limiter: self.rate_limiter.clone(),
+        }))
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = RateLimitFuture<B>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let rate_limiter = self.rate_limiter.clone();
+        let client = get_client(&req);
+        let route = req
+            .match_pattern()
+            .unwrap_or_else(|| req.path().to_string());
+        Box::pin(async move {
+            let route_prefix = format!("/{}/v1/", CONFIG.server.prefix);
+            let route = route.strip_prefix(&route_prefix).unwrap_or(&route);
+            if let Err(err) = rate_limiter.check_request(&client, route).await {
+                let response = err.error_response();
+                return Ok(req.into_response(response).map_into_right_body());
+            }
+            service
+                .call(req)
+                .await
+                .map(ServiceResponse::map_into_left_body)
+        })
+    }
+}
+
+fn get_client(req: &ServiceRequest) -> String {
+    if let Some(principal) = req.extensions().get::<Principal>() {
+        return format!("user:{}", principal.user);
+    }
+    // the forwarded address is only trusted behind a proxy that sets it
+    let ip = if CONFIG.rate_limit.use_forwarded_for {
+        req.connection_info().realip_remote_addr().map(String::from)
+    } else {
+        req.peer_addr().map(|addr| addr.ip().to_string())
+    };
+    format!("ip:{}", ip.unwrap_or_default())
+}
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize)]
+pub struct AuditLogParams {
+    pub id: Option<i32>,
+    pub page_size: Option<i64>,
+    pub actor: Option<String>,
+    pub action: Option<String>,
+}
@@ -0,0 +1,37 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::db::dao::admin_audit_dao::AdminAuditEntry;
+
+#[derive(Serialize)]
+pub struct AuditLogResponse {
+    pub id: i32,
+    pub actor: String,
+    // the API client that acted for the actor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    pub action: String,
+    pub parameters: Value,
+    pub result: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_hash: Option<String>,
+    pub timestamp: i64,
+}
+
+impl From<AdminAuditEntry> for AuditLogResponse {
+    fn from(entry: AdminAuditEntry) -> Self {
+        AuditLogResponse {
+            id: entry.id,
+            actor: entry.actor,
+            client: entry.client,
+            action: entry.action,
+            parameters: entry.parameters,
+            result: entry.result,
+            error: entry.error,
+            transaction_hash: entry.transaction_hash,
+            timestamp: entry.created_at.timestamp(),
+        }
+    }
+}
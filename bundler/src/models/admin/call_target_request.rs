@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct CallTargetRequest {
+    pub chain: String,
+    pub address: String,
+    #[serde(default)]
+    pub name: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct CallTargetsRequest {
+    pub chain: String,
+}
+
+impl CallTargetRequest {
+    pub fn get_chain(&self) -> String {
+        self.chain.to_lowercase()
+    }
+
+    pub fn get_address(&self) -> String {
+        self.address.to_lowercase()
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+}
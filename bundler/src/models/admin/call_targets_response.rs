@@ -0,0 +1,30 @@
+use serde::Serialize;
+
+use crate::db::dao::call_target_dao::CallTarget;
+
+#[derive(Serialize)]
+pub struct CallTargetsResponse {
+    pub chain: String,
+    pub targets: Vec<CallTargetResponse>,
+}
+
+#[derive(Serialize)]
+pub struct CallTargetResponse {
+    pub address: String,
+    pub name: String,
+}
+
+impl CallTargetsResponse {
+    pub fn new(chain: String, call_targets: Vec<CallTarget>) -> CallTargetsResponse {
+        CallTargetsResponse {
+            chain,
+            targets: call_targets
+                .into_iter()
+                .map(|call_target| CallTargetResponse {
+                    address: call_target.address,
+                    name: call_target.name,
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct DepositInfoResponse {
+    pub paymaster: String,
+    pub deposit: String,
+    pub staked: bool,
+    pub stake: String,
+    pub unstake_delay_sec: u32,
+    pub withdraw_time: u64,
+    pub currency: String,
+}
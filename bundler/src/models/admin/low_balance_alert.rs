@@ -0,0 +1,12 @@
+use ethers::types::Address;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct LowBalanceAlert {
+    pub chain: String,
+    pub entity: String,
+    pub address: Address,
+    pub balance: String,
+    pub threshold: String,
+    pub currency: String,
+}
@@ -1,3 +1,11 @@
 pub mod add_metadata_request;
+pub mod audit_log_params;
+pub mod audit_log_response;
+pub mod call_target_request;
+pub mod call_targets_response;
+pub mod deposit_info_response;
+pub mod low_balance_alert;
 pub mod metadata_response;
+pub mod paymaster_request;
 pub mod paymaster_topup;
+pub mod role;
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize)]
+pub struct PaymasterRequest {
+    pub chain: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PaymasterStakeRequest {
+    pub chain: String,
+    pub value: String,
+    pub unstake_delay_sec: u32,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PaymasterWithdrawRequest {
+    pub chain: String,
+    pub withdraw_address: String,
+    pub value: Option<String>,
+}
+
+impl PaymasterRequest {
+    pub fn get_chain(&self) -> String {
+        self.chain.to_lowercase()
+    }
+}
+
+impl PaymasterStakeRequest {
+    pub fn get_chain(&self) -> String {
+        self.chain.to_lowercase()
+    }
+
+    pub fn get_value(&self) -> String {
+        self.value.clone()
+    }
+}
+
+impl PaymasterWithdrawRequest {
+    pub fn get_chain(&self) -> String {
+        self.chain.to_lowercase()
+    }
+
+    pub fn get_withdraw_address(&self) -> String {
+        self.withdraw_address.to_lowercase()
+    }
+
+    pub fn get_value(&self) -> String {
+        self.value.clone().unwrap_or_default()
+    }
+}
@@ -1,7 +1,7 @@
 use crate::models::metadata::Metadata;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct PaymasterTopup {
     pub value: String,
     pub metadata: Metadata,
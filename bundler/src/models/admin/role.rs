@@ -0,0 +1,42 @@
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Permission {
+    Read,
+    ManageFunds,
+    ManageTokens,
+    ReadAuditLog,
+    Sponsor,
+}
+
+// `admin` is granted through the ADMIN env variable, the others under [admin_roles]. API
+// clients get theirs under [[auth.api_keys]].
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Admin,
+    Viewer,
+    Treasury,
+    TokenAdmin,
+    Auditor,
+    Sponsor,
+}
+
+impl Role {
+    pub fn permissions(&self) -> Vec<Permission> {
+        match self {
+            Role::Admin => vec![
+                Permission::Read,
+                Permission::ManageFunds,
+                Permission::ManageTokens,
+                Permission::ReadAuditLog,
+                Permission::Sponsor,
+            ],
+            Role::Viewer => vec![Permission::Read],
+            Role::Treasury => vec![Permission::Read, Permission::ManageFunds],
+            Role::TokenAdmin => vec![Permission::Read, Permission::ManageTokens],
+            Role::Auditor => vec![Permission::Read, Permission::ReadAuditLog],
+            Role::Sponsor => vec![Permission::Sponsor],
+        }
+    }
+}
@@ -2,6 +2,7 @@ use config::{Config, ConfigError, File, Map};
 use ethers::types::Address;
 use serde::Deserialize;
 
+use crate::models::admin::role::Role;
 use crate::models::config::env::ENV;
 
 #[derive(Debug, Deserialize, Clone)]
@@ -35,6 +36,62 @@ pub struct Chain {
     pub currency: String,
     pub entrypoint_address: Address,
     pub verifying_paymaster_address: Address,
+    pub fees: Fees,
+    pub confirmation_depth: u64,
+    pub sponsorship: SponsorshipRules,
+    pub deposits: DepositRules,
+    #[serde(default)]
+    pub token_paymasters: Map<String, Address>,
+}
+
+// Empty allowlists sponsor every currency and contract call target
+#[derive(Debug, Deserialize, Clone)]
+pub struct SponsorshipRules {
+    pub max_gas_per_op: u64,
+    pub user_daily_gas_budget: u64,
+    pub daily_spend_cap_gwei: u64,
+    #[serde(default)]
+    pub currencies: Vec<String>,
+    #[serde(default)]
+    pub targets: Vec<Address>,
+}
+
+// Thresholds below which the deposit monitor alerts, and how it refills the paymaster deposit
+#[derive(Debug, Deserialize, Clone)]
+pub struct DepositRules {
+    pub min_paymaster_deposit_gwei: u64,
+    pub min_relayer_balance_gwei: u64,
+    pub auto_top_up: bool,
+    pub top_up_gwei: u64,
+    pub max_daily_top_up_gwei: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum FeeStrategy {
+    Slow,
+    Standard,
+    Fast,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Fees {
+    pub strategy: FeeStrategy,
+    pub block_count: u64,
+    pub base_fee_multiplier_percent: u64,
+    pub min_priority_fee_per_gas: u64,
+    pub max_priority_fee_per_gas: u64,
+    pub max_fee_per_gas: u64,
+}
+
+impl FeeStrategy {
+    pub fn reward_percentile(&self) -> f64 {
+        match self {
+            FeeStrategy::Slow => 10.0,
+            FeeStrategy::Standard => 50.0,
+            FeeStrategy::Fast => 90.0,
+        }
+    }
 }
 
 impl Chain {
@@ -56,6 +113,143 @@ pub struct DefaultGas {
     pub max_priority_fee_per_gas: u64,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct GasEstimation {
+    pub verification_gas_margin_percent: u64,
+    pub call_gas_margin_percent: u64,
+    pub max_verification_gas: u64,
+    pub max_call_gas: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Mempool {
+    pub max_size: usize,
+    pub max_bundle_size: usize,
+    pub bundle_interval_ms: u64,
+    pub replacement_fee_bump_percent: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ReceiptTracker {
+    pub min_poll_interval_ms: u64,
+    pub max_poll_interval_ms: u64,
+    pub deadline_seconds: i64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Sponsorship {
+    pub validity_seconds: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct DepositMonitor {
+    pub poll_interval_seconds: u64,
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    pub webhook_timeout_ms: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SignerConfig {
+    Local {
+        private_key_env: String,
+    },
+    Keystore {
+        path: String,
+        password_env: String,
+    },
+    Remote {
+        url: String,
+        address: Address,
+        timeout_ms: u64,
+    },
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "algorithm", rename_all = "lowercase")]
+pub enum JwtKey {
+    Hs256 { secret_env: String },
+    Rs256 { jwks_path: String },
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Jwt {
+    pub issuer: String,
+    pub audience: String,
+    pub key: JwtKey,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ApiKey {
+    pub client: String,
+    pub key_env: String,
+    // roles of the client itself, never of the users it acts for
+    #[serde(default)]
+    pub roles: Vec<Role>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Auth {
+    pub jwt: Option<Jwt>,
+    #[serde(default)]
+    pub api_keys: Vec<ApiKey>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitBackend {
+    Memory,
+    Postgres,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct RouteRateLimit {
+    pub path: String,
+    pub requests_per_minute: u64,
+}
+
+// Values are in the currency's smallest unit
+#[derive(Debug, Deserialize, Clone)]
+pub struct TransferRateLimit {
+    pub transfers_per_day: u64,
+    #[serde(default)]
+    pub value_per_day: Map<String, String>,
+}
+
+// Counted per principal, or per client IP for unauthenticated requests
+#[derive(Debug, Deserialize, Clone)]
+pub struct RateLimit {
+    pub backend: RateLimitBackend,
+    pub requests_per_minute: u64,
+    #[serde(default)]
+    pub use_forwarded_for: bool,
+    #[serde(default)]
+    pub routes: Vec<RouteRateLimit>,
+    pub transfers: TransferRateLimit,
+}
+
+// Users granted each admin role, on top of the full admins in the ADMIN env variable
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct AdminRoles {
+    #[serde(default)]
+    pub viewer: Vec<String>,
+    #[serde(default)]
+    pub treasury: Vec<String>,
+    #[serde(default)]
+    pub token_admin: Vec<String>,
+    #[serde(default)]
+    pub auditor: Vec<String>,
+    #[serde(default)]
+    pub sponsor: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Signers {
+    pub relayer: SignerConfig,
+    pub verifying_paymaster: SignerConfig,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
     pub run_config: RunConfig,
@@ -63,7 +257,17 @@ pub struct Settings {
     pub server: Server,
     pub chains: Map<String, Chain>,
     pub default_gas: DefaultGas,
+    pub gas_estimation: GasEstimation,
+    pub mempool: Mempool,
+    pub receipt_tracker: ReceiptTracker,
+    pub sponsorship: Sponsorship,
+    pub deposit_monitor: DepositMonitor,
+    pub signers: Signers,
+    pub auth: Auth,
+    pub rate_limit: RateLimit,
     pub admins: Vec<String>,
+    #[serde(default)]
+    pub admin_roles: AdminRoles,
     pub env: ENV,
 }
 
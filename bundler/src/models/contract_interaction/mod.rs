@@ -1 +1,2 @@
 pub mod user_operation;
+pub mod user_operation_error;
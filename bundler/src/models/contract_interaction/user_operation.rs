@@ -1,3 +1,5 @@
+use crate::models::rpc::quantity;
+use crate::provider::fee_oracle::GasFees;
 use crate::CONFIG;
 use ethers::abi::AbiEncode;
 use ethers::contract::{Eip712, EthAbiType};
@@ -11,13 +13,20 @@ use std::ops::Deref;
 #[serde(rename_all = "camelCase")]
 pub struct UserOperation {
     pub sender: Address,
+    #[serde(with = "quantity")]
     pub nonce: u64,
     pub init_code: Bytes,
+    #[serde(rename = "callData")]
     pub calldata: Bytes,
+    #[serde(with = "quantity")]
     pub call_gas_limit: u64,
+    #[serde(with = "quantity")]
     pub verification_gas_limit: u64,
+    #[serde(with = "quantity")]
     pub pre_verification_gas: u64,
+    #[serde(with = "quantity")]
     pub max_fee_per_gas: u64,
+    #[serde(with = "quantity")]
     pub max_priority_fee_per_gas: u64,
     pub paymaster_and_data: Bytes,
     pub signature: Bytes,
@@ -66,6 +75,21 @@ impl UserOperation {
         self
     }
 
+    pub fn call_gas_limit(&mut self, call_gas_limit: u64) -> &mut UserOperation {
+        self.call_gas_limit = call_gas_limit;
+        self
+    }
+
+    pub fn verification_gas_limit(&mut self, verification_gas_limit: u64) -> &mut UserOperation {
+        self.verification_gas_limit = verification_gas_limit;
+        self
+    }
+
+    pub fn pre_verification_gas(&mut self, pre_verification_gas: u64) -> &mut UserOperation {
+        self.pre_verification_gas = pre_verification_gas;
+        self
+    }
+
     pub fn calldata(&mut self, calldata: Bytes) -> &mut UserOperation {
         self.calldata = calldata;
         self
@@ -76,19 +100,19 @@ impl UserOperation {
         self
     }
 
+    pub fn gas_fees(&mut self, gas_fees: GasFees) -> &mut UserOperation {
+        self.max_fee_per_gas = gas_fees.max_fee_per_gas.low_u64();
+        self.max_priority_fee_per_gas = gas_fees.max_priority_fee_per_gas.low_u64();
+        self
+    }
+
     pub fn sender(&mut self, wallet_address: Address) -> &mut UserOperation {
         self.sender = wallet_address;
         self
     }
 
-    pub fn paymaster_and_data(
-        &mut self,
-        data: ethers::abi::Bytes,
-        paymaster: Address,
-        sign: Option<Vec<u8>>,
-    ) -> &mut UserOperation {
-        self.paymaster_and_data =
-            Bytes::from([paymaster.as_bytes(), &data, &sign.unwrap_or(vec![0u8; 65])].concat());
+    pub fn paymaster_and_data(&mut self, paymaster_and_data: Bytes) -> &mut UserOperation {
+        self.paymaster_and_data = paymaster_and_data;
         self
     }
 
@@ -0,0 +1,41 @@
+use derive_more::Display;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Display, Serialize)]
+#[display(fmt = "{}", reason)]
+pub struct UserOperationError {
+    pub code: i64,
+    pub reason: String,
+}
+
+impl UserOperationError {
+    pub const REJECTED_BY_ENTRYPOINT: i64 = -32500;
+    pub const REJECTED_BY_PAYMASTER: i64 = -32501;
+    pub const OUT_OF_TIME_RANGE: i64 = -32503;
+    pub const INVALID_SIGNATURE: i64 = -32507;
+    pub const REJECTED_BY_MEMPOOL: i64 = -32602;
+
+    pub fn new(code: i64, reason: String) -> UserOperationError {
+        UserOperationError { code, reason }
+    }
+
+    pub fn from_failed_op(reason: String) -> UserOperationError {
+        let code = if reason.starts_with("AA22") || reason.starts_with("AA32") {
+            Self::OUT_OF_TIME_RANGE
+        } else if reason.starts_with("AA3") {
+            Self::REJECTED_BY_PAYMASTER
+        } else {
+            Self::REJECTED_BY_ENTRYPOINT
+        };
+        Self::new(code, reason)
+    }
+
+    // the "AAxx" prefix the EntryPoint puts on FailedOp reasons
+    pub fn entrypoint_code(&self) -> Option<String> {
+        let prefix = self.reason.get(0..4)?;
+        if prefix.starts_with("AA") && prefix[2..].chars().all(|c| c.is_ascii_digit()) {
+            return Some(prefix.to_string());
+        }
+        None
+    }
+}
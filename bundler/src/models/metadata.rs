@@ -10,4 +10,8 @@ impl Metadata {
     pub fn get_currency(&self) -> String {
         self.currency.clone().to_lowercase()
     }
+
+    pub fn get_chain(&self) -> String {
+        self.chain.clone().to_lowercase()
+    }
 }
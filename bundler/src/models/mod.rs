@@ -4,7 +4,9 @@ pub mod contract_interaction;
 pub mod currency;
 pub mod hello_world;
 pub mod metadata;
+pub mod principal;
 pub mod response;
+pub mod rpc;
 pub mod transaction;
 pub mod transaction_type;
 pub mod transfer;
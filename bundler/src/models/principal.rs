@@ -0,0 +1,72 @@
+use std::future::{ready, Ready};
+
+use actix_web::dev::Payload;
+use actix_web::{FromRequest, HttpMessage, HttpRequest};
+
+use crate::errors::ApiError;
+use crate::models::admin::role::{Permission, Role};
+use crate::CONFIG;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AuthMethod {
+    Jwt,
+    ApiKey,
+}
+
+// The verified caller, set by the authentication middleware. An API client names the user it
+// acts for, so that user is only as trustworthy as the client.
+#[derive(Clone, Debug)]
+pub struct Principal {
+    pub user: String,
+    pub client: Option<String>,
+    pub auth_method: AuthMethod,
+}
+
+impl Principal {
+    // Only JWT subjects hold the roles configured for their user. An API client holds the
+    // roles configured for the client, whichever user it names.
+    pub fn get_roles(&self) -> Vec<Role> {
+        if self.auth_method == AuthMethod::ApiKey {
+            return CONFIG
+                .auth
+                .api_keys
+                .iter()
+                .find(|api_key| Some(&api_key.client) == self.client.as_ref())
+                .map(|api_key| api_key.roles.clone())
+                .unwrap_or_default();
+        }
+        let admin_roles = &CONFIG.admin_roles;
+        [
+            (Role::Admin, CONFIG.get_admins()),
+            (Role::Viewer, &admin_roles.viewer),
+            (Role::Treasury, &admin_roles.treasury),
+            (Role::TokenAdmin, &admin_roles.token_admin),
+            (Role::Auditor, &admin_roles.auditor),
+            (Role::Sponsor, &admin_roles.sponsor),
+        ]
+        .into_iter()
+        .filter(|(_, users)| users.contains(&self.user))
+        .map(|(role, _)| role)
+        .collect()
+    }
+
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.get_roles()
+            .iter()
+            .any(|role| role.permissions().contains(&permission))
+    }
+}
+
+impl FromRequest for Principal {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<Principal>()
+                .cloned()
+                .ok_or(ApiError::Unauthorized("Missing credentials".to_string())),
+        )
+    }
+}
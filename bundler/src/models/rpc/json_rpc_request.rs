@@ -0,0 +1,29 @@
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::models::rpc::json_rpc_response::JsonRpcError;
+
+#[derive(Deserialize)]
+pub struct JsonRpcRequest {
+    pub jsonrpc: String,
+    #[serde(default)]
+    pub id: Value,
+    pub method: String,
+    #[serde(default)]
+    pub params: Vec<Value>,
+}
+
+impl JsonRpcRequest {
+    pub fn param<T>(&self, index: usize) -> Result<T, JsonRpcError>
+    where
+        T: DeserializeOwned,
+    {
+        let value = self.params.get(index).cloned().ok_or_else(|| {
+            JsonRpcError::invalid_params(format!("missing param at index {}", index))
+        })?;
+        serde_json::from_value(value).map_err(|err| {
+            JsonRpcError::invalid_params(format!("invalid param at index {}: {}", index, err))
+        })
+    }
+}
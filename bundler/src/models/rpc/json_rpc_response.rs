@@ -0,0 +1,91 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::models::contract_interaction::user_operation_error::UserOperationError;
+
+#[derive(Serialize)]
+pub struct JsonRpcResponse {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(flatten)]
+    pub outcome: JsonRpcOutcome,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonRpcOutcome {
+    Result(Value),
+    Error(JsonRpcError),
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl JsonRpcResponse {
+    pub fn new(id: Value, outcome: Result<Value, JsonRpcError>) -> JsonRpcResponse {
+        JsonRpcResponse {
+            jsonrpc: String::from("2.0"),
+            id,
+            outcome: match outcome {
+                Ok(result) => JsonRpcOutcome::Result(result),
+                Err(error) => JsonRpcOutcome::Error(error),
+            },
+        }
+    }
+}
+
+impl JsonRpcError {
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const INTERNAL_ERROR: i64 = -32603;
+    pub const UNAUTHORIZED: i64 = -32001;
+
+    pub fn new(code: i64, message: String) -> JsonRpcError {
+        JsonRpcError {
+            code,
+            message,
+            data: None,
+        }
+    }
+
+    pub fn invalid_request(message: String) -> JsonRpcError {
+        Self::new(Self::INVALID_REQUEST, message)
+    }
+
+    pub fn method_not_found(method: &str) -> JsonRpcError {
+        Self::new(
+            Self::METHOD_NOT_FOUND,
+            format!("Method {} is not supported", method),
+        )
+    }
+
+    pub fn invalid_params(message: String) -> JsonRpcError {
+        Self::new(Self::INVALID_PARAMS, message)
+    }
+
+    pub fn internal(message: String) -> JsonRpcError {
+        Self::new(Self::INTERNAL_ERROR, message)
+    }
+
+    pub fn unauthorized(message: String) -> JsonRpcError {
+        Self::new(Self::UNAUTHORIZED, message)
+    }
+}
+
+impl From<UserOperationError> for JsonRpcError {
+    fn from(error: UserOperationError) -> Self {
+        JsonRpcError {
+            code: error.code,
+            data: error
+                .entrypoint_code()
+                .map(|code| json!({ "reason": error.reason, "code": code })),
+            message: error.reason,
+        }
+    }
+}
@@ -0,0 +1,7 @@
+pub mod json_rpc_request;
+pub mod json_rpc_response;
+pub mod quantity;
+pub mod sponsor_user_operation_response;
+pub mod user_operation_gas_estimate;
+pub mod user_operation_receipt;
+pub mod user_operation_response;
@@ -0,0 +1,84 @@
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serializer};
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Quantity {
+    Number(u64),
+    String(String),
+}
+
+pub fn serialize<S>(value: &u64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&format!("{:#x}", value))
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Quantity::deserialize(deserializer)? {
+        Quantity::Number(value) => Ok(value),
+        Quantity::String(value) => match value.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => value.parse::<u64>(),
+        }
+        .map_err(|err| D::Error::custom(format!("invalid quantity {}: {}", value, err))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Gas {
+        #[serde(with = "crate::models::rpc::quantity")]
+        value: u64,
+    }
+
+    fn parse(value: serde_json::Value) -> Result<u64, serde_json::Error> {
+        serde_json::from_value::<Gas>(json!({ "value": value })).map(|gas| gas.value)
+    }
+
+    #[test]
+    fn serializes_as_hex() {
+        let gas = Gas { value: 21000 };
+        assert_eq!(
+            serde_json::to_value(&gas).unwrap(),
+            json!({"value": "0x5208"})
+        );
+        assert_eq!(
+            serde_json::to_value(Gas { value: 0 }).unwrap(),
+            json!({"value": "0x0"})
+        );
+    }
+
+    #[test]
+    fn parses_hex_decimal_and_numbers() {
+        assert_eq!(parse(json!("0x5208")).unwrap(), 21000);
+        assert_eq!(parse(json!("0x0")).unwrap(), 0);
+        assert_eq!(parse(json!("21000")).unwrap(), 21000);
+        assert_eq!(parse(json!(21000)).unwrap(), 21000);
+        assert_eq!(parse(json!("0xffffffffffffffff")).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn rejects_invalid_quantities() {
+        assert!(parse(json!("0x")).is_err());
+        assert!(parse(json!("0xzz")).is_err());
+        assert!(parse(json!("-1")).is_err());
+        assert!(parse(json!("0x10000000000000000")).is_err());
+        assert!(parse(json!(null)).is_err());
+    }
+
+    #[test]
+    fn round_trips() {
+        let gas = Gas { value: 123456789 };
+        let serialized = serde_json::to_string(&gas).unwrap();
+        assert_eq!(serde_json::from_str::<Gas>(&serialized).unwrap(), gas);
+    }
+}
@@ -0,0 +1,13 @@
+use ethers::types::{Bytes, U256};
+use serde::Serialize;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SponsorUserOperationResponse {
+    pub paymaster_and_data: Bytes,
+    pub pre_verification_gas: U256,
+    pub verification_gas_limit: U256,
+    pub call_gas_limit: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+}
@@ -0,0 +1,17 @@
+use ethers::types::{Address, Log, TransactionReceipt, H256, U256};
+use serde::Serialize;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationReceipt {
+    pub user_op_hash: H256,
+    pub entry_point: Address,
+    pub sender: Address,
+    pub nonce: U256,
+    pub paymaster: Address,
+    pub actual_gas_cost: U256,
+    pub actual_gas_used: U256,
+    pub success: bool,
+    pub logs: Vec<Log>,
+    pub receipt: TransactionReceipt,
+}
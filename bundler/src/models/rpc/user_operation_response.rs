@@ -0,0 +1,14 @@
+use ethers::types::{Address, H256, U64};
+use serde::Serialize;
+
+use crate::models::contract_interaction::user_operation::UserOperation;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationResponse {
+    pub user_operation: UserOperation,
+    pub entry_point: Address,
+    pub transaction_hash: H256,
+    pub block_hash: H256,
+    pub block_number: U64,
+}
@@ -1,7 +1,19 @@
 use serde::Deserialize;
 
+use crate::CONFIG;
+
 #[derive(Deserialize)]
 pub struct ListTransactionsParams {
     pub id: Option<i32>,
     pub page_size: Option<i64>,
+    pub chain: Option<String>,
+}
+
+impl ListTransactionsParams {
+    pub fn get_chain(&self) -> String {
+        self.chain
+            .clone()
+            .unwrap_or(CONFIG.run_config.current_chain.clone())
+            .to_lowercase()
+    }
 }
@@ -1,3 +1,4 @@
+use crate::constants::Constants;
 use crate::db::dao::transaction_dao::UserTransaction;
 use crate::provider::helpers::get_explorer_url;
 use bigdecimal::BigDecimal;
@@ -13,6 +14,8 @@ pub struct Transaction {
     pub to: UserInfo,
     #[serde(rename = "type")]
     pub transaction_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_id: Option<String>,
 }
 
 #[derive(Default, Serialize, Deserialize)]
@@ -32,6 +35,9 @@ pub struct UserInfo {
 pub struct Metadata {
     pub chain: String,
     pub gas: Amount,
+    pub gas_used: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
     pub transaction_hash: String,
     pub timestamp: i64,
     pub explorer_url: String,
@@ -48,11 +54,20 @@ impl From<UserTransaction> for Transaction {
                 exponent: transaction.exponent,
             },
             metadata: Metadata {
+                explorer_url: get_explorer_url(
+                    &transaction.metadata.chain,
+                    &transaction.metadata.transaction_hash,
+                ),
                 chain: transaction.metadata.chain,
-                gas: Amount::default(),
+                gas: Amount {
+                    currency: transaction.metadata.gas.currency,
+                    value: transaction.metadata.gas.value.to_string().parse().unwrap(),
+                    exponent: Constants::NATIVE_EXPONENT,
+                },
+                gas_used: transaction.metadata.gas_used,
+                revert_reason: transaction.metadata.revert_reason,
                 transaction_hash: transaction.metadata.transaction_hash.clone(),
                 timestamp: transaction.updated_at.timestamp(),
-                explorer_url: get_explorer_url(&transaction.metadata.transaction_hash),
                 status: transaction.status,
             },
             from: UserInfo {
@@ -65,6 +80,7 @@ impl From<UserTransaction> for Transaction {
                 name: transaction.metadata.to_name,
             },
             transaction_type: transaction.transaction_type,
+            batch_id: transaction.batch_id,
         }
     }
 }
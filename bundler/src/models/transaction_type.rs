@@ -1,11 +1,13 @@
 pub enum TransactionType {
     Debit,
+    Call,
 }
 
 impl TransactionType {
     pub fn to_string(&self) -> String {
         match self {
             Self::Debit => String::from("debit"),
+            Self::Call => String::from("call"),
         }
     }
 }
@@ -0,0 +1,86 @@
+use ethers::types::U256;
+
+use crate::errors::ApiError;
+use crate::provider::helpers::{parse_base_units, parse_units};
+
+// What a transfer moves: a `value` in the token's base units, or an `amount` of whole tokens
+// such as "12.5"
+pub enum Amount {
+    BaseUnits(String),
+    Tokens(String),
+}
+
+impl Amount {
+    pub fn from(value: Option<String>, amount: Option<String>) -> Result<Amount, ApiError> {
+        match (value, amount) {
+            (Some(value), None) => Ok(Amount::BaseUnits(value)),
+            (None, Some(amount)) => Ok(Amount::Tokens(amount)),
+            (Some(_), Some(_)) => Err(ApiError::BadRequest(
+                "Only one of value and amount can be given".to_string(),
+            )),
+            (None, None) => Err(ApiError::BadRequest(
+                "Either value or amount is required".to_string(),
+            )),
+        }
+    }
+
+    pub fn to_base_units(&self, exponent: i32) -> Result<U256, ApiError> {
+        let base_units = match self {
+            Amount::BaseUnits(value) => parse_base_units(value),
+            Amount::Tokens(amount) => parse_units(amount, exponent),
+        };
+        base_units.map_err(ApiError::BadRequest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::U256;
+
+    use super::Amount;
+    use crate::errors::ApiError;
+
+    fn bad_request(result: Result<Amount, ApiError>) -> String {
+        match result {
+            Err(ApiError::BadRequest(message)) => message,
+            _ => panic!("expected a bad request"),
+        }
+    }
+
+    #[test]
+    fn takes_either_value_or_amount() {
+        let value = Amount::from(Some("1000".to_string()), None);
+        assert!(matches!(value, Ok(Amount::BaseUnits(value)) if value == "1000"));
+        let amount = Amount::from(None, Some("1.5".to_string()));
+        assert!(matches!(amount, Ok(Amount::Tokens(amount)) if amount == "1.5"));
+    }
+
+    #[test]
+    fn rejects_both_or_neither() {
+        assert_eq!(
+            bad_request(Amount::from(Some("1".to_string()), Some("1".to_string()))),
+            "Only one of value and amount can be given"
+        );
+        assert_eq!(
+            bad_request(Amount::from(None, None)),
+            "Either value or amount is required"
+        );
+    }
+
+    #[test]
+    fn scales_tokens_by_the_exponent() {
+        let amount = Amount::Tokens("1.5".to_string());
+        assert_eq!(amount.to_base_units(6).ok(), Some(U256::from(1_500_000)));
+        assert_eq!(
+            amount.to_base_units(18).ok(),
+            Some(U256::from(1_500_000_000_000_000_000u64))
+        );
+    }
+
+    #[test]
+    fn keeps_base_units_whatever_the_exponent() {
+        let value = Amount::BaseUnits("1500000".to_string());
+        assert_eq!(value.to_base_units(6).ok(), Some(U256::from(1_500_000)));
+        assert_eq!(value.to_base_units(18).ok(), Some(U256::from(1_500_000)));
+    }
+}
@@ -0,0 +1,38 @@
+use serde::Deserialize;
+
+use crate::errors::ApiError;
+use crate::models::transfer::amount::Amount;
+
+#[derive(Deserialize)]
+pub struct BatchTransferRequest {
+    pub transfers: Vec<BatchTransfer>,
+    pub chain: String,
+}
+
+#[derive(Deserialize)]
+pub struct BatchTransfer {
+    pub receiver: String,
+    pub value: Option<String>,
+    pub amount: Option<String>,
+    pub currency: String,
+}
+
+impl BatchTransferRequest {
+    pub fn get_chain(&self) -> String {
+        self.chain.clone().to_lowercase()
+    }
+}
+
+impl BatchTransfer {
+    pub fn get_receiver(&self) -> String {
+        self.receiver.clone().to_lowercase()
+    }
+
+    pub fn get_amount(&self) -> Result<Amount, ApiError> {
+        Amount::from(self.value.clone(), self.amount.clone())
+    }
+
+    pub fn get_currency(&self) -> String {
+        self.currency.clone().to_lowercase()
+    }
+}
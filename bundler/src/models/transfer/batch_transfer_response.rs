@@ -0,0 +1,9 @@
+use crate::models::transfer::transaction_response::TransactionResponse;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct BatchTransferResponse {
+    pub transaction: TransactionResponse,
+    pub batch_id: String,
+    pub transaction_ids: Vec<String>,
+}
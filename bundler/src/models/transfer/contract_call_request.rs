@@ -0,0 +1,31 @@
+use serde::Deserialize;
+
+// `data` is raw calldata; otherwise `function` is a signature such as
+// `approve(address,uint256)` encoded with `args`
+#[derive(Deserialize)]
+pub struct ContractCallRequest {
+    pub target: String,
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub data: Option<String>,
+    #[serde(default)]
+    pub function: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub chain: String,
+}
+
+impl ContractCallRequest {
+    pub fn get_target(&self) -> String {
+        self.target.clone().to_lowercase()
+    }
+
+    pub fn get_value(&self) -> String {
+        self.value.clone().unwrap_or(String::from("0"))
+    }
+
+    pub fn get_chain(&self) -> String {
+        self.chain.clone().to_lowercase()
+    }
+}
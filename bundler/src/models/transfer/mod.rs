@@ -1,3 +1,7 @@
+pub mod amount;
+pub mod batch_transfer_request;
+pub mod batch_transfer_response;
+pub mod contract_call_request;
 pub mod status;
 pub mod transaction_response;
 pub mod transfer_request;
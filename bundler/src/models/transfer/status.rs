@@ -1,14 +1,22 @@
 pub enum Status {
+    CONFIRMED,
+    DROPPED,
     FAILED,
+    INCLUDED,
     PENDING,
+    SUBMITTED,
     SUCCESS,
 }
 
 impl Status {
     pub fn to_string(&self) -> String {
         match self {
+            Status::CONFIRMED => String::from("confirmed"),
+            Status::DROPPED => String::from("dropped"),
             Status::FAILED => String::from("failed"),
+            Status::INCLUDED => String::from("included"),
             Status::PENDING => String::from("pending"),
+            Status::SUBMITTED => String::from("submitted"),
             Status::SUCCESS => String::from("success"),
         }
     }
@@ -1,11 +1,19 @@
-use crate::models::metadata::Metadata;
+use ethers::types::H256;
+use ethers::utils::keccak256;
 use serde::Deserialize;
+use serde_json::json;
+
+use crate::errors::ApiError;
+use crate::models::metadata::Metadata;
+use crate::models::transfer::amount::Amount;
 
 #[derive(Deserialize)]
 pub struct TransferRequest {
     pub receiver: String,
-    pub value: String,
+    pub value: Option<String>,
+    pub amount: Option<String>,
     pub metadata: Metadata,
+    pub gas_currency: Option<String>,
 }
 
 impl TransferRequest {
@@ -13,7 +21,26 @@ impl TransferRequest {
         self.receiver.clone().to_lowercase()
     }
 
-    pub fn get_value(&self) -> String {
-        self.value.clone()
+    pub fn get_amount(&self) -> Result<Amount, ApiError> {
+        Amount::from(self.value.clone(), self.amount.clone())
+    }
+
+    pub fn get_gas_currency(&self) -> Option<String> {
+        self.gas_currency
+            .clone()
+            .map(|gas_currency| gas_currency.to_lowercase())
+    }
+
+    // identifies the transfer for idempotency checks
+    pub fn get_request_hash(&self) -> String {
+        let request = json!({
+            "receiver": self.get_receiver(),
+            "value": self.value,
+            "amount": self.amount,
+            "currency": self.metadata.get_currency(),
+            "chain": self.metadata.get_chain(),
+            "gas_currency": self.get_gas_currency(),
+        });
+        format!("{:?}", H256::from(keccak256(request.to_string())))
     }
 }
@@ -0,0 +1,17 @@
+use serde::Deserialize;
+
+use crate::CONFIG;
+
+#[derive(Deserialize)]
+pub struct AddressRequest {
+    pub chain: Option<String>,
+}
+
+impl AddressRequest {
+    pub fn get_chain(&self) -> String {
+        self.chain
+            .clone()
+            .unwrap_or(CONFIG.run_config.current_chain.clone())
+            .to_lowercase()
+    }
+}
@@ -1,13 +1,13 @@
 use base64::engine::general_purpose;
 use base64::Engine;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize)]
 pub struct BalanceRequest {
     pub q: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct Balance {
     pub chain: String,
     pub currency: String,
@@ -1,3 +1,4 @@
+pub mod address_request;
 pub mod address_response;
 pub mod balance_request;
 pub mod balance_response;
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+
+use actix_web::http::header::{HeaderName, AUTHORIZATION};
+use actix_web::HttpRequest;
+use ethers::utils::keccak256;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use log::{debug, warn};
+use serde::Deserialize;
+
+use crate::errors::ApiError;
+use crate::models::config::settings::JwtKey;
+use crate::models::principal::{AuthMethod, Principal};
+use crate::CONFIG;
+
+#[derive(Deserialize)]
+struct Claims {
+    sub: String,
+}
+
+// Verifies bearer JWTs and per-client API keys. API keys are looked up by their hash, and the
+// client names the user it acts for in the `user` header. Admin roles come from the JWT subject
+// or the client itself, never from that header.
+#[derive(Clone)]
+pub struct Authenticator {
+    jwt_keys: Arc<Vec<(Option<String>, DecodingKey)>>,
+    jwt_validation: Option<Validation>,
+    api_keys: Arc<HashMap<[u8; 32], String>>,
+}
+
+impl Authenticator {
+    pub fn init() -> Authenticator {
+        let (jwt_keys, jwt_validation) = match &CONFIG.auth.jwt {
+            Some(jwt) => {
+                let (algorithm, keys) = match &jwt.key {
+                    JwtKey::Hs256 { secret_env } => {
+                        let secret = std::env::var(secret_env)
+                            .unwrap_or_else(|_| panic!("{} env variable not set", secret_env));
+                        (
+                            Algorithm::HS256,
+                            vec![(None, DecodingKey::from_secret(secret.as_bytes()))],
+                        )
+                    }
+                    JwtKey::Rs256 { jwks_path } => {
+                        let jwks: JwkSet = serde_json::from_str(
+                            &fs::read_to_string(jwks_path).expect("Failed to read JWKS file"),
+                        )
+                        .expect("Invalid JWKS file");
+                        let keys = jwks
+                            .keys
+                            .iter()
+                            .map(|jwk| {
+                                (
+                                    jwk.common.key_id.clone(),
+                                    DecodingKey::from_jwk(jwk).expect("Invalid JWK"),
+                                )
+                            })
+                            .collect();
+                        (Algorithm::RS256, keys)
+                    }
+                };
+                let mut validation = Validation::new(algorithm);
+                validation.set_issuer(&[&jwt.issuer]);
+                validation.set_audience(&[&jwt.audience]);
+                (keys, Some(validation))
+            }
+            None => (vec![], None),
+        };
+        let api_keys = CONFIG
+            .auth
+            .api_keys
+            .iter()
+            .map(|api_key| {
+                let key = std::env::var(&api_key.key_env)
+                    .unwrap_or_else(|_| panic!("{} env variable not set", api_key.key_env));
+                (keccak256(key.as_bytes()), api_key.client.clone())
+            })
+            .collect();
+        Authenticator {
+            jwt_keys: Arc::new(jwt_keys),
+            jwt_validation,
+            api_keys: Arc::new(api_keys),
+        }
+    }
+
+    // None when the request carries no credentials at all
+    pub fn authenticate(&self, req: &HttpRequest) -> Result<Option<Principal>, ApiError> {
+        if let Some(authorization) = req.headers().get(AUTHORIZATION) {
+            let token = authorization
+                .to_str()
+                .ok()
+                .and_then(|authorization| authorization.strip_prefix("Bearer "));
+            return match token {
+                Some(token) => self.verify_jwt(token).map(Some),
+                None => Err(ApiError::Unauthorized(
+                    "Invalid authorization header".to_string(),
+                )),
+            };
+        }
+        if let Some(api_key) = req.headers().get(HeaderName::from_static("x-api-key")) {
+            return self.verify_api_key(api_key.as_bytes(), req).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn verify_jwt(&self, token: &str) -> Result<Principal, ApiError> {
+        let validation = match &self.jwt_validation {
+            Some(validation) => validation,
+            None => return Err(ApiError::Unauthorized("Invalid token".to_string())),
+        };
+        let header = decode_header(token)
+            .map_err(|_| ApiError::Unauthorized("Invalid token".to_string()))?;
+        let key = self
+            .jwt_keys
+            .iter()
+            .find(|(key_id, _)| key_id.is_none() || *key_id == header.kid)
+            .map(|(_, key)| key);
+        let key = match key {
+            Some(key) => key,
+            None => return Err(ApiError::Unauthorized("Unknown signing key".to_string())),
+        };
+        match decode::<Claims>(token, key, validation) {
+            Ok(token) => Ok(Principal {
+                user: token.claims.sub,
+                client: None,
+                auth_method: AuthMethod::Jwt,
+            }),
+            Err(err) => {
+                warn!("Token rejected: {:?}", err);
+                Err(ApiError::Unauthorized("Invalid token".to_string()))
+            }
+        }
+    }
+
+    fn verify_api_key(&self, api_key: &[u8], req: &HttpRequest) -> Result<Principal, ApiError> {
+        let client = match self.api_keys.get(&keccak256(api_key)) {
+            Some(client) => client,
+            None => return Err(ApiError::Unauthorized("Invalid API key".to_string())),
+        };
+        let user = req
+            .headers()
+            .get(HeaderName::from_static("user"))
+            .and_then(|user| user.to_str().ok())
+            .filter(|user| !user.is_empty());
+        match user {
+            Some(user) => {
+                debug!("API client {} acting for {}", client, user);
+                Ok(Principal {
+                    user: user.to_string(),
+                    client: Some(client.clone()),
+                    auth_method: AuthMethod::ApiKey,
+                })
+            }
+            None => Err(ApiError::Unauthorized("Missing user".to_string())),
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ethers::providers::{Http, Provider};
+
+use crate::bundler::bundler::Bundler;
+use crate::bundler::gas_estimator::GasEstimator;
+use crate::contracts::entrypoint_provider::EntryPointProvider;
+use crate::contracts::erc20_provider_registry::Erc20ProviderRegistry;
+use crate::contracts::simple_account_factory_provider::SimpleAccountFactoryProvider;
+use crate::contracts::simple_account_provider::SimpleAccountProvider;
+use crate::contracts::token_paymaster_provider::TokenPaymasterProvider;
+use crate::errors::ApiError;
+use crate::provider::paymaster_provider::PaymasterProvider;
+use crate::signer::middleware_signer::RelayerClient;
+use crate::CONFIG;
+
+#[derive(Clone)]
+pub struct ChainContext {
+    pub chain: String,
+    pub client: Arc<Provider<Http>>,
+    pub entrypoint_provider: EntryPointProvider,
+    pub simple_account_factory_provider: SimpleAccountFactoryProvider,
+    pub simple_account_provider: SimpleAccountProvider,
+    pub verifying_paymaster_provider: PaymasterProvider,
+    pub erc20_provider_registry: Erc20ProviderRegistry,
+    pub token_paymaster_providers: HashMap<String, TokenPaymasterProvider>,
+    pub relayer_signer: Arc<RelayerClient>,
+    pub bundler: Bundler,
+    pub gas_estimator: GasEstimator,
+}
+
+// One context per configured chain, keyed by the chain name used in requests
+#[derive(Clone)]
+pub struct ChainRegistry {
+    chains: Arc<HashMap<String, ChainContext>>,
+}
+
+impl ChainRegistry {
+    pub fn new(contexts: Vec<ChainContext>) -> ChainRegistry {
+        ChainRegistry {
+            chains: Arc::new(
+                contexts
+                    .into_iter()
+                    .map(|context| (context.chain.clone(), context))
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn get(&self, chain: &str) -> Result<&ChainContext, ApiError> {
+        match self.chains.get(&chain.to_lowercase()) {
+            Some(context) => Ok(context),
+            None => Err(ApiError::BadRequest("Chain not supported".to_string())),
+        }
+    }
+
+    pub fn get_default(&self) -> &ChainContext {
+        &self.chains[&CONFIG.run_config.current_chain]
+    }
+}
@@ -0,0 +1,60 @@
+use ethers::providers::Middleware;
+use ethers::types::{BlockNumber, U256};
+use log::error;
+
+use crate::{CONFIG, PROVIDERS};
+
+#[derive(Clone, Debug)]
+pub struct GasFees {
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+}
+
+#[derive(Clone)]
+pub struct FeeOracle {}
+
+impl FeeOracle {
+    pub async fn get_fees(chain: &str) -> Result<GasFees, String> {
+        let fees = &CONFIG.chains[chain].fees;
+        let result = PROVIDERS[chain]
+            .fee_history(
+                fees.block_count,
+                BlockNumber::Latest,
+                &[fees.strategy.reward_percentile()],
+            )
+            .await;
+        if result.is_err() {
+            error!("Fee history failed: {:?}", result.err().unwrap());
+            return Err(String::from("Failed to get fee history"));
+        }
+        let fee_history = result.unwrap();
+
+        // the last entry is the base fee of the pending block
+        let base_fee = match fee_history.base_fee_per_gas.last() {
+            None => return Err(String::from("Base fee not available")),
+            Some(base_fee) => *base_fee,
+        };
+
+        let mut rewards: Vec<U256> = fee_history
+            .reward
+            .iter()
+            .filter_map(|block_rewards| block_rewards.first().copied())
+            .filter(|reward| !reward.is_zero())
+            .collect();
+        rewards.sort();
+        let priority_fee = rewards
+            .get(rewards.len() / 2)
+            .copied()
+            .unwrap_or_default()
+            .max(U256::from(fees.min_priority_fee_per_gas))
+            .min(U256::from(fees.max_priority_fee_per_gas));
+
+        let max_fee = (base_fee * fees.base_fee_multiplier_percent / 100 + priority_fee)
+            .min(U256::from(fees.max_fee_per_gas));
+
+        Ok(GasFees {
+            max_fee_per_gas: max_fee,
+            max_priority_fee_per_gas: priority_fee.min(max_fee),
+        })
+    }
+}
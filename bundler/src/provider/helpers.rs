@@ -1,18 +1,19 @@
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
-
-use actix_web::http::header::HeaderName;
 use actix_web::web::Json;
 use actix_web::HttpRequest;
+use bigdecimal::BigDecimal;
+use ethers::abi::token::{LenientTokenizer, Tokenizer};
+use ethers::abi::{Abi, AbiDecode, AbiParser};
 use ethers::providers::Middleware;
-use ethers::types::Address;
+use ethers::types::{Address, Bytes, U256};
+use ethers::utils::keccak256;
 use rand::distributions::Alphanumeric;
 use rand::Rng;
 use serde::Serialize;
 
+use crate::constants::Constants;
 use crate::errors::ApiError;
 use crate::models::response::base_response::BaseResponse;
-use crate::{CONFIG, PROVIDER};
+use crate::{CONFIG, PROVIDERS};
 
 pub fn respond_json<T>(data: T) -> Result<Json<BaseResponse<T>>, ApiError>
 where
@@ -24,24 +25,81 @@ where
     }))
 }
 
-pub fn get_user(req: HttpRequest) -> String {
-    req.headers()
-        .get(HeaderName::from_static("user"))
-        .unwrap()
-        .to_str()
-        .unwrap()
-        .to_string()
+pub fn get_idempotency_key(req: &HttpRequest) -> Result<Option<String>, ApiError> {
+    let idempotency_key = match req.headers().get(Constants::IDEMPOTENCY_KEY_HEADER) {
+        Some(idempotency_key) => idempotency_key.to_str().unwrap_or_default(),
+        None => return Ok(None),
+    };
+    if idempotency_key.is_empty() || idempotency_key.len() > Constants::MAX_IDEMPOTENCY_KEY_LENGTH {
+        return Err(ApiError::BadRequest("Invalid Idempotency-Key".to_string()));
+    }
+    Ok(Some(idempotency_key.to_string()))
+}
+
+// keccak256(namespace || user id || attempt), the attempt being a fixed 8 bytes
+pub fn get_salt(user_id: &str, attempt: u64) -> U256 {
+    U256::from_big_endian(&keccak256(
+        [
+            Constants::SALT_NAMESPACE.as_bytes(),
+            user_id.as_bytes(),
+            &attempt.to_be_bytes(),
+        ]
+        .concat(),
+    ))
 }
 
-pub fn get_hash(s: String) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    s.hash(&mut hasher);
-    hasher.finish()
+// an amount of whole tokens such as "12.5" in base units of a token with the given exponent
+pub fn parse_units(amount: &str, exponent: i32) -> Result<U256, String> {
+    parse_decimal(
+        "Amount",
+        amount,
+        usize::try_from(exponent).unwrap_or_default(),
+    )
 }
 
-pub async fn contract_exists_at(address: String) -> bool {
+pub fn parse_base_units(value: &str) -> Result<U256, String> {
+    if value.contains('.') {
+        return Err("Value is in base units and cannot have decimals".to_string());
+    }
+    parse_decimal("Value", value, 0)
+}
+
+fn parse_decimal(field: &str, decimal: &str, exponent: usize) -> Result<U256, String> {
+    let decimal = decimal.trim();
+    if decimal.starts_with('-') {
+        return Err(format!("{} cannot be negative", field));
+    }
+    let (whole, fraction) = decimal.split_once('.').unwrap_or((decimal, ""));
+    let is_numeric = |digits: &str| digits.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_numeric(whole) || !is_numeric(fraction) {
+        return Err(format!("{} is not a number: {}", field, decimal));
+    }
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > exponent {
+        return Err(format!(
+            "{} has more than {} decimal places",
+            field, exponent
+        ));
+    }
+    let base_units = format!(
+        "{}{}{}",
+        whole,
+        fraction,
+        "0".repeat(exponent - fraction.len())
+    );
+    U256::from_dec_str(&base_units).map_err(|_| format!("{} is too large", field))
+}
+
+pub fn to_u256(value: &BigDecimal) -> Result<U256, String> {
+    U256::from_dec_str(&value.with_scale(0).to_string()).map_err(|err| err.to_string())
+}
+
+pub async fn contract_exists_at(chain: &str, address: String) -> bool {
     let formatted_address: Address = address.parse().unwrap();
-    let code = PROVIDER.get_code(formatted_address, None).await.unwrap();
+    let code = PROVIDERS[chain]
+        .get_code(formatted_address, None)
+        .await
+        .unwrap();
     !code.is_empty()
 }
 
@@ -55,6 +113,161 @@ pub fn generate_txn_id() -> String {
     format!("{}_{}", prefix, id).to_string()
 }
 
-pub fn get_explorer_url(txn_hash: &str) -> String {
-    CONFIG.get_chain().explorer_url.clone() + &txn_hash.clone()
+pub fn decode_revert_reason(revert_reason: &Bytes, abis: &[&Abi]) -> String {
+    if revert_reason.len() < 4 {
+        return String::from("reverted without a reason");
+    }
+    let (selector, data) = revert_reason.split_at(4);
+    if selector == Constants::ERROR_STRING_SELECTOR {
+        if let Ok(reason) = String::decode(data) {
+            return reason;
+        }
+    }
+    if selector == Constants::PANIC_SELECTOR {
+        if let Ok(code) = U256::decode(data) {
+            return format!("panic: {:#x}", code);
+        }
+    }
+    for abi in abis {
+        for error in abi.errors() {
+            if error.signature()[..4] != *selector {
+                continue;
+            }
+            if let Ok(tokens) = error.decode(data) {
+                let params: Vec<String> = tokens.iter().map(|token| token.to_string()).collect();
+                return format!("{}({})", error.name, params.join(", "));
+            }
+        }
+    }
+    format!("{}", revert_reason)
+}
+
+pub fn encode_function_call(signature: &str, args: &[String]) -> Result<Bytes, String> {
+    let function = AbiParser::default()
+        .parse_function(signature)
+        .map_err(|err| format!("Invalid function signature: {}", err))?;
+    if function.inputs.len() != args.len() {
+        return Err(format!(
+            "{} expects {} arguments",
+            function.name,
+            function.inputs.len()
+        ));
+    }
+    let mut tokens = vec![];
+    for (param, arg) in function.inputs.iter().zip(args) {
+        let token = LenientTokenizer::tokenize(&param.kind, arg)
+            .map_err(|_| format!("Invalid value for {}: {}", param.kind, arg))?;
+        tokens.push(token);
+    }
+    function
+        .encode_input(&tokens)
+        .map(Bytes::from)
+        .map_err(|err| err.to_string())
+}
+
+pub fn get_explorer_url(chain: &str, txn_hash: &str) -> String {
+    match CONFIG.chains.get(chain) {
+        Some(chain) => chain.explorer_url.clone() + txn_hash,
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bigdecimal::BigDecimal;
+    use ethers::types::U256;
+
+    use super::{get_salt, parse_base_units, parse_decimal, parse_units, to_u256};
+
+    #[test]
+    fn salt_is_pinned() {
+        assert_eq!(
+            format!("{:x}", get_salt("alice@example.com", 0)),
+            "dda88aacd52bb7f30b51b903d7cd5de5ebfa51da3483a750074fdd60d16bf55"
+        );
+        assert_eq!(
+            get_salt("alice@example.com", 1),
+            U256::from_dec_str(
+                "109169549593604100945083898680300884978565613025940081575957759103527570920112"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn salt_round_trips_through_big_decimal() {
+        for attempt in 0..3 {
+            let salt = get_salt("alice@example.com", attempt);
+            let stored = BigDecimal::from_str(&salt.to_string()).unwrap();
+            assert_eq!(to_u256(&stored).unwrap(), salt);
+        }
+        assert_eq!(
+            to_u256(&BigDecimal::from_str(&U256::MAX.to_string()).unwrap()).unwrap(),
+            U256::MAX
+        );
+    }
+
+    #[test]
+    fn parses_decimals_into_base_units() {
+        assert_eq!(
+            parse_decimal("Amount", "12.5", 6),
+            Ok(U256::from(12_500_000))
+        );
+        assert_eq!(parse_decimal("Amount", " 0.000001 ", 6), Ok(U256::one()));
+        assert_eq!(parse_decimal("Amount", "3", 0), Ok(U256::from(3)));
+        assert_eq!(parse_decimal("Amount", ".5", 1), Ok(U256::from(5)));
+        assert_eq!(parse_decimal("Amount", "7.", 2), Ok(U256::from(700)));
+        assert_eq!(parse_decimal("Amount", "1.2300", 2), Ok(U256::from(123)));
+    }
+
+    #[test]
+    fn rejects_invalid_decimals() {
+        assert_eq!(
+            parse_decimal("Amount", "-1", 6),
+            Err("Amount cannot be negative".to_string())
+        );
+        assert_eq!(
+            parse_decimal("Amount", "1.0000001", 6),
+            Err("Amount has more than 6 decimal places".to_string())
+        );
+        for invalid in ["", ".", "abc", "1.2.3", "1e6", "+1", "0x10"] {
+            assert_eq!(
+                parse_decimal("Amount", invalid, 6),
+                Err(format!("Amount is not a number: {}", invalid))
+            );
+        }
+        assert_eq!(
+            parse_decimal("Amount", &U256::MAX.to_string(), 1),
+            Err("Amount is too large".to_string())
+        );
+        assert_eq!(
+            parse_decimal("Amount", &format!("{}0", U256::MAX), 0),
+            Err("Amount is too large".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_units_and_base_units() {
+        assert_eq!(parse_units("1.5", 18), Ok(U256::exp10(18) * 3 / 2));
+        assert_eq!(
+            parse_units("0.1", 0),
+            Err("Amount has more than 0 decimal places".to_string())
+        );
+        assert_eq!(parse_base_units("1000"), Ok(U256::from(1000)));
+        assert_eq!(parse_base_units(&U256::MAX.to_string()), Ok(U256::MAX));
+        assert_eq!(
+            parse_base_units("1.0"),
+            Err("Value is in base units and cannot have decimals".to_string())
+        );
+        assert_eq!(
+            parse_base_units("-5"),
+            Err("Value cannot be negative".to_string())
+        );
+        assert_eq!(
+            parse_base_units(&format!("{}0", U256::MAX)),
+            Err("Value is too large".to_string())
+        );
+    }
 }
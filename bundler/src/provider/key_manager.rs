@@ -0,0 +1,74 @@
+use std::fs;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ethers_signers::LocalWallet;
+use log::error;
+
+// Owner keys are encrypted with AES-256-GCM and stored as base64(nonce || ciphertext)
+#[derive(Clone)]
+pub struct KeyManager {
+    cipher: Aes256Gcm,
+}
+
+impl KeyManager {
+    const NONCE_SIZE: usize = 12;
+
+    pub fn init() -> KeyManager {
+        let master_key = match std::env::var("KEY_ENCRYPTION_KEY") {
+            Ok(master_key) => master_key,
+            Err(_) => fs::read_to_string(
+                std::env::var("KEY_ENCRYPTION_KEY_FILE")
+                    .expect("KEY_ENCRYPTION_KEY or KEY_ENCRYPTION_KEY_FILE must be set"),
+            )
+            .expect("KEY_ENCRYPTION_KEY_FILE must be readable"),
+        };
+        let master_key = ethers::utils::hex::decode(master_key.trim())
+            .expect("KEY_ENCRYPTION_KEY must be hex encoded");
+        if master_key.len() != 32 {
+            panic!("KEY_ENCRYPTION_KEY must be 32 bytes");
+        }
+        KeyManager {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&master_key)),
+        }
+    }
+
+    pub fn generate(&self) -> Result<(LocalWallet, String), String> {
+        let wallet = LocalWallet::new(&mut OsRng);
+        let encrypted_key = self.encrypt(&wallet.signer().to_bytes())?;
+        Ok((wallet, encrypted_key))
+    }
+
+    pub fn decrypt(&self, encrypted_key: &str) -> Result<LocalWallet, String> {
+        let data = STANDARD.decode(encrypted_key).map_err(|err| {
+            error!("Owner key decoding failed: {:?}", err);
+            String::from("invalid owner key")
+        })?;
+        if data.len() <= Self::NONCE_SIZE {
+            return Err(String::from("invalid owner key"));
+        }
+        let (nonce, ciphertext) = data.split_at(Self::NONCE_SIZE);
+        let key = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                error!("Owner key decryption failed");
+                String::from("failed to decrypt owner key")
+            })?;
+        LocalWallet::from_bytes(&key).map_err(|err| {
+            error!("Owner key is not a valid private key: {:?}", err);
+            String::from("invalid owner key")
+        })
+    }
+
+    fn encrypt(&self, key: &[u8]) -> Result<String, String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self.cipher.encrypt(&nonce, key).map_err(|_| {
+            error!("Owner key encryption failed");
+            String::from("failed to encrypt owner key")
+        })?;
+        Ok(STANDARD.encode([nonce.as_slice(), &ciphertext].concat()))
+    }
+}
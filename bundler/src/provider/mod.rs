@@ -1,5 +1,11 @@
+pub mod authenticator;
+pub mod chain_registry;
+pub mod fee_oracle;
 pub mod helpers;
-pub mod listeners;
+pub mod key_manager;
 pub mod paymaster_provider;
+pub mod paymaster_sponsor;
+pub mod rate_limiter;
+pub mod sponsorship_policy;
 pub mod verifying_paymaster_helper;
 pub mod web3_provider;
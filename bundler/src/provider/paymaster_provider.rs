@@ -1,5 +1,6 @@
+use ethers::abi::Abi;
 use ethers::providers::{Http, Provider};
-use ethers::utils::format_ether;
+use ethers::types::{Address, Bytes, U256};
 use log::error;
 
 use crate::provider::verifying_paymaster_helper::{UserOperation, VerifyingPaymaster};
@@ -10,7 +11,46 @@ pub struct PaymasterProvider {
 }
 
 impl PaymasterProvider {
-    pub async fn get_deposit(&self) -> Result<String, String> {
+    pub fn abi(&self) -> &Abi {
+        self.provider.abi()
+    }
+
+    pub fn add_stake(&self, unstake_delay_sec: u32) -> Result<Bytes, String> {
+        let data = self.provider.add_stake(unstake_delay_sec).calldata();
+        if data.is_none() {
+            return Err(String::from("add stake data failed"));
+        }
+        Ok(data.unwrap())
+    }
+
+    pub fn unlock_stake(&self) -> Result<Bytes, String> {
+        let data = self.provider.unlock_stake().calldata();
+        if data.is_none() {
+            return Err(String::from("unlock stake data failed"));
+        }
+        Ok(data.unwrap())
+    }
+
+    pub fn withdraw_stake(&self, withdraw_address: Address) -> Result<Bytes, String> {
+        let data = self.provider.withdraw_stake(withdraw_address).calldata();
+        if data.is_none() {
+            return Err(String::from("withdraw stake data failed"));
+        }
+        Ok(data.unwrap())
+    }
+
+    pub fn withdraw_to(&self, withdraw_address: Address, amount: U256) -> Result<Bytes, String> {
+        let data = self
+            .provider
+            .withdraw_to(withdraw_address, amount)
+            .calldata();
+        if data.is_none() {
+            return Err(String::from("withdraw data failed"));
+        }
+        Ok(data.unwrap())
+    }
+
+    pub async fn get_deposit(&self) -> Result<U256, String> {
         let response = self.provider.get_deposit().await;
         if response.is_err() {
             error!(
@@ -19,7 +59,7 @@ impl PaymasterProvider {
             );
             return Err(String::from("Failed to get balance"));
         }
-        Ok(format_ether(response.unwrap()))
+        Ok(response.unwrap())
     }
 
     pub async fn get_hash(
@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use ethers::abi::{encode, Tokenizable};
+use ethers::types::{Bytes, H256};
+
+use crate::contracts::token_paymaster_provider::TokenPaymasterProvider;
+use crate::models::contract_interaction::user_operation::UserOperation;
+use crate::provider::paymaster_provider::PaymasterProvider;
+use crate::provider::sponsorship_policy::{SponsorshipPolicy, SponsorshipScope};
+use crate::provider::verifying_paymaster_helper::get_verifying_paymaster_user_operation_payload;
+use crate::signer::Signer;
+use crate::CONFIG;
+
+#[derive(Debug)]
+pub enum SponsorshipError {
+    Denied(String),
+    Failed(String),
+}
+
+// Gas is either sponsored by the VerifyingPaymaster within the given scope, or paid by the
+// wallet in tokens through a TokenPaymaster
+pub enum GasPayment {
+    Sponsored(SponsorshipScope),
+    Token(TokenPaymasterProvider),
+}
+
+// Builds the VerifyingPaymaster's paymasterAndData:
+// paymaster address ‖ abi.encode(valid_until, valid_after) ‖ signature
+#[derive(Clone)]
+pub struct PaymasterSponsor {
+    pub signer: Arc<dyn Signer>,
+    pub sponsorship_policy: SponsorshipPolicy,
+}
+
+impl PaymasterSponsor {
+    // A well-formed signature from the wrong signer lets simulations run past the paymaster
+    pub async fn get_dummy_paymaster_and_data(
+        &self,
+        chain: &str,
+        validity_window: (u64, u64),
+    ) -> Result<Bytes, SponsorshipError> {
+        let signature = self
+            .signer
+            .sign_hash(H256::zero())
+            .await
+            .map_err(SponsorshipError::Failed)?;
+        Ok(Self::pack(chain, validity_window, signature.to_vec()))
+    }
+
+    // The returned reservation is recorded once the operation is queued, or released
+    pub async fn sponsor(
+        &self,
+        chain: &str,
+        paymaster_provider: &PaymasterProvider,
+        user_op: &UserOperation,
+        scope: &SponsorshipScope,
+        validity_window: (u64, u64),
+    ) -> Result<(Bytes, String), SponsorshipError> {
+        let reservation = self
+            .sponsorship_policy
+            .evaluate(chain, user_op, scope)
+            .await
+            .map_err(SponsorshipError::Denied)?;
+        let signature = self
+            .sign(chain, paymaster_provider, user_op, validity_window)
+            .await;
+        if signature.is_err() {
+            self.sponsorship_policy.release(reservation).await;
+            return Err(signature.err().unwrap());
+        }
+        Ok((signature.unwrap(), reservation))
+    }
+
+    async fn sign(
+        &self,
+        chain: &str,
+        paymaster_provider: &PaymasterProvider,
+        user_op: &UserOperation,
+        validity_window: (u64, u64),
+    ) -> Result<Bytes, SponsorshipError> {
+        let (valid_until, valid_after) = validity_window;
+        let hash = paymaster_provider
+            .get_hash(
+                get_verifying_paymaster_user_operation_payload(user_op.clone()),
+                valid_until,
+                valid_after,
+            )
+            .await
+            .map_err(SponsorshipError::Failed)?;
+        let signature = self
+            .signer
+            .sign_message(&hash)
+            .await
+            .map_err(SponsorshipError::Failed)?;
+        Ok(Self::pack(chain, validity_window, signature.to_vec()))
+    }
+
+    fn pack(chain: &str, validity_window: (u64, u64), signature: Vec<u8>) -> Bytes {
+        let (valid_until, valid_after) = validity_window;
+        let data = encode(&[valid_until.into_token(), valid_after.into_token()]);
+        Bytes::from(
+            [
+                CONFIG.chains[chain].verifying_paymaster_address.as_bytes(),
+                &data,
+                &signature,
+            ]
+            .concat(),
+        )
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use bigdecimal::BigDecimal;
+use ethers::types::U256;
+use log::error;
+
+use crate::constants::Constants;
+use crate::db::dao::rate_limit_dao::{RateLimitCounter, RateLimitDao};
+use crate::errors::ApiError;
+use crate::models::config::settings::RateLimitBackend;
+use crate::CONFIG;
+
+// key -> (window start, window length, total)
+type Counters = HashMap<String, (u64, u64, U256)>;
+
+// In memory counters are per instance, the Postgres ones are shared by every instance
+#[derive(Clone)]
+enum RateLimitStore {
+    Memory(Arc<Mutex<Counters>>),
+    Postgres(RateLimitDao),
+}
+
+// Fixed window counters for requests per minute and transfers and transfer value per day
+#[derive(Clone)]
+pub struct RateLimiter {
+    store: RateLimitStore,
+    route_limits: Arc<HashMap<String, u64>>,
+    value_limits: Arc<HashMap<String, U256>>,
+}
+
+impl RateLimiter {
+    pub fn init(rate_limit_dao: RateLimitDao) -> RateLimiter {
+        let rate_limit = &CONFIG.rate_limit;
+        let store = match rate_limit.backend {
+            RateLimitBackend::Memory => {
+                RateLimitStore::Memory(Arc::new(Mutex::new(HashMap::new())))
+            }
+            RateLimitBackend::Postgres => RateLimitStore::Postgres(rate_limit_dao),
+        };
+        RateLimiter {
+            store,
+            route_limits: Arc::new(
+                rate_limit
+                    .routes
+                    .iter()
+                    .map(|route| (route.path.clone(), route.requests_per_minute))
+                    .collect(),
+            ),
+            value_limits: Arc::new(
+                rate_limit
+                    .transfers
+                    .value_per_day
+                    .iter()
+                    .map(|(currency, limit)| {
+                        (
+                            currency.to_lowercase(),
+                            U256::from_dec_str(limit).expect("Invalid transfer value limit"),
+                        )
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    // route is the path below /{prefix}/v1/. Request limits fail open when the shared counters
+    // are unavailable.
+    pub async fn check_request(&self, client: &str, route: &str) -> Result<(), ApiError> {
+        let mut counters = vec![];
+        if let Some(limit) = self.route_limits.get(route) {
+            counters.push(Counter::new(
+                format!("requests:{}:{}", route, client),
+                U256::one(),
+                U256::from(*limit),
+                Constants::MINUTE_SECONDS,
+                "Too many requests",
+            ));
+        }
+        counters.push(Counter::new(
+            format!("requests:{}", client),
+            U256::one(),
+            U256::from(CONFIG.rate_limit.requests_per_minute),
+            Constants::MINUTE_SECONDS,
+            "Too many requests",
+        ));
+        match self.acquire(&counters).await {
+            Ok(result) => result,
+            Err(_) => Ok(()),
+        }
+    }
+
+    // Transfers are (currency, value) pairs in the currency's smallest unit. The returned
+    // quota goes back through `refund` if the transfers are not made after all. Transfer
+    // limits fail closed when the shared counters are unavailable.
+    pub async fn check_transfers(
+        &self,
+        user: &str,
+        chain: &str,
+        transfers: &[(String, String)],
+    ) -> Result<TransferQuota, ApiError> {
+        let mut values: HashMap<String, U256> = HashMap::new();
+        for (currency, value) in transfers {
+            let value = match U256::from_dec_str(value) {
+                Ok(value) => value,
+                Err(_) => return Err(ApiError::BadRequest("Invalid value".to_string())),
+            };
+            let total = values.entry(currency.to_lowercase()).or_default();
+            *total = total.saturating_add(value);
+        }
+        let mut counters = vec![];
+        for (currency, value) in values {
+            if let Some(limit) = self.value_limits.get(&currency) {
+                counters.push(Counter::new(
+                    format!("transfer_value:{}:{}:{}", chain, currency, user),
+                    value,
+                    *limit,
+                    Constants::DAY_SECONDS,
+                    &format!("Daily {} transfer limit reached", currency),
+                ));
+            }
+        }
+        counters.push(Counter::new(
+            format!("transfers:{}", user),
+            U256::from(transfers.len()),
+            U256::from(CONFIG.rate_limit.transfers.transfers_per_day),
+            Constants::DAY_SECONDS,
+            "Daily transfer limit reached",
+        ));
+        match self.acquire(&counters).await {
+            Ok(result) => result.map(|_| TransferQuota { counters }),
+            Err(_) => Err(ApiError::TooManyRequests(
+                "Transfer limits are unavailable, try again later".to_string(),
+                Constants::MINUTE_SECONDS,
+            )),
+        }
+    }
+
+    pub async fn refund(&self, quota: TransferQuota) {
+        match &self.store {
+            RateLimitStore::Memory(counters) => {
+                let mut entries = counters.lock().unwrap();
+                for counter in &quota.counters {
+                    if let Some(entry) = entries.get_mut(&counter.key) {
+                        if entry.0 == counter.window_start {
+                            entry.2 = entry.2.saturating_sub(counter.amount);
+                        }
+                    }
+                }
+            }
+            RateLimitStore::Postgres(rate_limit_dao) => {
+                rate_limit_dao
+                    .release(&Self::to_rate_limit_counters(&quota.counters))
+                    .await
+            }
+        }
+    }
+
+    // Either every counter takes its amount or none does. The outer error means the counters
+    // could not be checked at all.
+    async fn acquire(&self, counters: &[Counter]) -> Result<Result<(), ApiError>, String> {
+        let rejected = match counters
+            .iter()
+            .position(|counter| counter.amount > counter.limit)
+        {
+            Some(index) => Some(index),
+            None => match &self.store {
+                RateLimitStore::Memory(entries) => Self::acquire_in_memory(entries, counters),
+                RateLimitStore::Postgres(rate_limit_dao) => rate_limit_dao
+                    .acquire(&Self::to_rate_limit_counters(counters))
+                    .await
+                    .map_err(|err| {
+                        error!("Rate limit counters unavailable: {}", err);
+                        err
+                    })?,
+            },
+        };
+        Ok(match rejected {
+            Some(index) => {
+                let counter = &counters[index];
+                let now = Self::now();
+                Err(ApiError::TooManyRequests(
+                    counter.message.clone(),
+                    (counter.window_start + counter.window).saturating_sub(now),
+                ))
+            }
+            None => Ok(()),
+        })
+    }
+
+    fn acquire_in_memory(entries: &Mutex<Counters>, counters: &[Counter]) -> Option<usize> {
+        let mut entries = entries.lock().unwrap();
+        if entries.len() >= Constants::MAX_IN_MEMORY_RATE_LIMIT_KEYS {
+            let now = Self::now();
+            entries.retain(|_, (start, length, _)| *start + *length > now);
+        }
+        let total = |entries: &Counters, counter: &Counter| match entries.get(&counter.key) {
+            Some((start, _, total)) if *start == counter.window_start => *total,
+            _ => U256::zero(),
+        };
+        let rejected = counters.iter().position(|counter| {
+            total(&entries, counter).saturating_add(counter.amount) > counter.limit
+        });
+        if rejected.is_none() {
+            for counter in counters {
+                let total = total(&entries, counter) + counter.amount;
+                entries.insert(
+                    counter.key.clone(),
+                    (counter.window_start, counter.window, total),
+                );
+            }
+        }
+        rejected
+    }
+
+    fn to_rate_limit_counters(counters: &[Counter]) -> Vec<RateLimitCounter> {
+        counters
+            .iter()
+            .map(|counter| RateLimitCounter {
+                key: counter.key.clone(),
+                window_start: counter.window_start,
+                amount: BigDecimal::from_str(&counter.amount.to_string()).unwrap(),
+                limit: BigDecimal::from_str(&counter.limit.to_string()).unwrap(),
+            })
+            .collect()
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+}
+
+// A fixed window counter, the window being the one current when it was created
+#[derive(Clone)]
+struct Counter {
+    key: String,
+    amount: U256,
+    limit: U256,
+    window_start: u64,
+    window: u64,
+    message: String,
+}
+
+impl Counter {
+    fn new(key: String, amount: U256, limit: U256, window: u64, message: &str) -> Counter {
+        let now = RateLimiter::now();
+        Counter {
+            key,
+            amount,
+            limit,
+            window_start: now - now % window,
+            window,
+            message: message.to_string(),
+        }
+    }
+}
+
+// What a set of transfers took from the transfer limits
+pub struct TransferQuota {
+    counters: Vec<Counter>,
+}
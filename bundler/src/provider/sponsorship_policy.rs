@@ -0,0 +1,267 @@
+use std::time::SystemTime;
+
+use bigdecimal::BigDecimal;
+use ethers::abi::AbiDecode;
+use ethers::types::{Address, Bytes, H256, U256};
+
+use crate::contracts::erc20_provider::ERC20Calls;
+use crate::contracts::simple_account_provider::SimpleAccountCalls;
+use crate::db::dao::sponsorship_dao::{Reservation, Sponsorship, SponsorshipDao};
+use crate::db::dao::token_metadata_dao::TokenMetadata;
+use crate::models::contract_interaction::user_operation::UserOperation;
+use crate::models::currency::Currency;
+use crate::CONFIG;
+
+// What an operation asks the paymaster to sponsor, beyond its gas
+#[derive(Clone, Default)]
+pub struct SponsorshipScope {
+    pub currencies: Vec<String>,
+    pub targets: Vec<Address>,
+}
+
+impl SponsorshipScope {
+    // Reads the scope off the calls a wallet makes through `execute` or `executeBatch`. Token
+    // transfers count as their currency, any other call as its target.
+    pub fn from_call_data(
+        call_data: &Bytes,
+        tokens: &[TokenMetadata],
+    ) -> Result<SponsorshipScope, String> {
+        let calls: Vec<(Address, U256, Bytes)> = match SimpleAccountCalls::decode(call_data) {
+            Ok(SimpleAccountCalls::Execute(call)) => vec![(call.dest, call.value, call.func)],
+            Ok(SimpleAccountCalls::ExecuteBatch(call)) if call.dest.len() == call.func.len() => {
+                call.dest
+                    .into_iter()
+                    .zip(call.func)
+                    .map(|(dest, func)| (dest, U256::zero(), func))
+                    .collect()
+            }
+            _ => {
+                return Err(String::from(
+                    "Only execute and executeBatch calls are sponsored",
+                ))
+            }
+        };
+        let native = tokens.iter().find(|token| {
+            matches!(
+                Currency::from_str(token.token_type.clone()),
+                Some(Currency::Native)
+            )
+        });
+        let mut scope = SponsorshipScope::default();
+        for (dest, value, func) in calls {
+            if !value.is_zero() {
+                match native {
+                    Some(native) => scope.currencies.push(native.symbol.clone()),
+                    None => return Err(String::from("Native transfers are not sponsored")),
+                }
+            }
+            let token = tokens.iter().find(|token| {
+                matches!(
+                    Currency::from_str(token.token_type.clone()),
+                    Some(Currency::Erc20)
+                ) && token.contract_address.parse::<Address>().ok() == Some(dest)
+            });
+            match token {
+                Some(token) if matches!(ERC20Calls::decode(&func), Ok(ERC20Calls::Transfer(_))) => {
+                    scope.currencies.push(token.symbol.clone())
+                }
+                // a plain native transfer calls nothing
+                None if func.is_empty() && !value.is_zero() => {}
+                _ => scope.targets.push(dest),
+            }
+        }
+        Ok(scope)
+    }
+}
+
+#[derive(Clone)]
+pub struct SponsorshipPolicy {
+    pub sponsorship_dao: SponsorshipDao,
+}
+
+impl SponsorshipPolicy {
+    // (valid_until, valid_after) for the paymaster signature
+    pub fn get_validity_window() -> (u64, u64) {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        (now + CONFIG.sponsorship.validity_seconds, now)
+    }
+
+    // Budgets count the gas an operation may use, the actual cost is only known once it lands.
+    // An accepted operation reserves its share of them and gets back the reservation key.
+    pub async fn evaluate(
+        &self,
+        chain: &str,
+        user_op: &UserOperation,
+        scope: &SponsorshipScope,
+    ) -> Result<String, String> {
+        let rules = &CONFIG.chains[chain].sponsorship;
+        if !rules.currencies.is_empty() {
+            if let Some(currency) = scope
+                .currencies
+                .iter()
+                .find(|currency| !rules.currencies.contains(currency))
+            {
+                return Err(format!("{} transfers are not sponsored", currency));
+            }
+        }
+        if !rules.targets.is_empty() {
+            if let Some(target) = scope
+                .targets
+                .iter()
+                .find(|target| !rules.targets.contains(target))
+            {
+                return Err(format!("Calls to {:?} are not sponsored", target));
+            }
+        }
+
+        let gas_limit = Self::get_gas_limit(user_op);
+        if gas_limit > rules.max_gas_per_op {
+            return Err(format!(
+                "Gas limit {} exceeds the sponsored maximum of {} per operation",
+                gas_limit, rules.max_gas_per_op
+            ));
+        }
+
+        let reservation = format!("{:?}", H256::random());
+        let spend_cap =
+            BigDecimal::from(rules.daily_spend_cap_gwei) * BigDecimal::from(1_000_000_000);
+        let result = self
+            .sponsorship_dao
+            .reserve_sponsorship(
+                &Sponsorship {
+                    reservation: reservation.clone(),
+                    chain: chain.to_string(),
+                    sender: format!("{:?}", user_op.sender),
+                    gas_limit,
+                    max_cost: Self::get_max_cost(user_op),
+                },
+                rules.user_daily_gas_budget,
+                &spend_cap,
+            )
+            .await?;
+        match result {
+            Reservation::Reserved => Ok(reservation),
+            Reservation::GasBudgetExhausted => Err(format!(
+                "Daily gas budget of {} is exhausted",
+                rules.user_daily_gas_budget
+            )),
+            Reservation::SpendCapReached => {
+                Err(format!("Daily sponsorship cap reached on {}", chain))
+            }
+        }
+    }
+
+    // the reservation made by `evaluate` is kept under the hash of the sponsored operation
+    pub async fn record(&self, reservation: String, user_op_hash: H256) {
+        self.sponsorship_dao
+            .record_sponsorship(reservation, format!("{:?}", user_op_hash))
+            .await;
+    }
+
+    // an operation that was sponsored but never queued gives its reservation back
+    pub async fn release(&self, reservation: String) {
+        self.sponsorship_dao.release_sponsorship(reservation).await;
+    }
+
+    fn get_gas_limit(user_op: &UserOperation) -> u64 {
+        user_op.call_gas_limit + user_op.verification_gas_limit + user_op.pre_verification_gas
+    }
+
+    fn get_max_cost(user_op: &UserOperation) -> BigDecimal {
+        let max_cost =
+            U256::from(Self::get_gas_limit(user_op)) * U256::from(user_op.max_fee_per_gas);
+        max_cost.to_string().parse().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::AbiEncode;
+    use ethers::types::{Address, Bytes, U256};
+
+    use super::SponsorshipScope;
+    use crate::contracts::erc20_provider::TransferCall;
+    use crate::contracts::simple_account_provider::{ExecuteBatchCall, ExecuteCall};
+    use crate::db::dao::token_metadata_dao::TokenMetadata;
+
+    fn token(symbol: &str, contract_address: Address, token_type: &str) -> TokenMetadata {
+        TokenMetadata {
+            chain: String::from("test"),
+            symbol: symbol.to_string(),
+            contract_address: format!("{:?}", contract_address),
+            exponent: 6,
+            token_type: token_type.to_string(),
+            name: symbol.to_string(),
+            created_at: None,
+            updated_at: None,
+            is_supported: true,
+        }
+    }
+
+    fn tokens() -> Vec<TokenMetadata> {
+        vec![
+            token("eth", Address::zero(), "native"),
+            token("usdc", Address::from_low_u64_be(1), "erc20"),
+        ]
+    }
+
+    fn transfer() -> Bytes {
+        Bytes::from(
+            TransferCall {
+                to: Address::from_low_u64_be(9),
+                value: U256::from(5),
+            }
+            .encode(),
+        )
+    }
+
+    #[test]
+    fn token_transfers_count_as_their_currency() {
+        let call_data = ExecuteCall {
+            dest: Address::from_low_u64_be(1),
+            value: U256::zero(),
+            func: transfer(),
+        }
+        .encode();
+        let scope = SponsorshipScope::from_call_data(&Bytes::from(call_data), &tokens()).unwrap();
+        assert_eq!(scope.currencies, vec!["usdc"]);
+        assert!(scope.targets.is_empty());
+    }
+
+    #[test]
+    fn other_calls_count_as_their_target() {
+        let call_data = ExecuteBatchCall {
+            dest: vec![Address::from_low_u64_be(2), Address::from_low_u64_be(1)],
+            func: vec![transfer(), Bytes::from(vec![0x09, 0x5e, 0xa7, 0xb3])],
+        }
+        .encode();
+        let scope = SponsorshipScope::from_call_data(&Bytes::from(call_data), &tokens()).unwrap();
+        assert!(scope.currencies.is_empty());
+        assert_eq!(
+            scope.targets,
+            vec![Address::from_low_u64_be(2), Address::from_low_u64_be(1)]
+        );
+    }
+
+    #[test]
+    fn native_value_counts_as_the_native_currency() {
+        let call_data = ExecuteCall {
+            dest: Address::from_low_u64_be(9),
+            value: U256::from(1),
+            func: Bytes::default(),
+        }
+        .encode();
+        let scope = SponsorshipScope::from_call_data(&Bytes::from(call_data), &tokens()).unwrap();
+        assert_eq!(scope.currencies, vec!["eth"]);
+        assert!(scope.targets.is_empty());
+    }
+
+    #[test]
+    fn denies_undecodable_call_data() {
+        let call_data = Bytes::from(vec![0xde, 0xad, 0xbe, 0xef]);
+        assert!(SponsorshipScope::from_call_data(&call_data, &tokens()).is_err());
+    }
+}
@@ -1,16 +1,18 @@
 use ethers::abi::Abi;
+use ethers::middleware::nonce_manager::NonceManagerError;
 use ethers::middleware::signer::SignerMiddlewareError;
-use ethers::middleware::SignerMiddleware;
 use ethers::prelude::ProviderError;
 use ethers::providers::{Http, Middleware, Provider};
-use ethers::types::{Address, Bytes, TransactionRequest};
-use ethers_signers::LocalWallet;
-use log::error;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, BlockNumber, Bytes, Eip1559TransactionRequest, TransactionRequest};
+use log::{error, warn};
 use serde_json::Value;
 use std::num::ParseIntError;
 use std::sync::Arc;
 
-use crate::PROVIDER;
+use crate::provider::fee_oracle::FeeOracle;
+use crate::signer::middleware_signer::RelayerClient;
+use crate::PROVIDERS;
 
 #[derive(Clone)]
 pub struct Web3Provider {}
@@ -21,8 +23,8 @@ impl Web3Provider {
         provider
     }
 
-    pub async fn get_balance(address: Address) -> Result<String, String> {
-        let result = PROVIDER.get_balance(address, None).await;
+    pub async fn get_balance(chain: &str, address: Address) -> Result<String, String> {
+        let result = PROVIDERS[chain].get_balance(address, None).await;
         if result.is_err() {
             error!("Get native balance failed: {:?}", result.err().unwrap());
             return Err(String::from("Failed to get balance"));
@@ -35,7 +37,8 @@ impl Web3Provider {
     }
 
     pub async fn execute(
-        signer: SignerMiddleware<Arc<Provider<Http>>, LocalWallet>,
+        signer: Arc<RelayerClient>,
+        chain: &str,
         to: Address,
         value: String,
         data: Bytes,
@@ -45,15 +48,36 @@ impl Web3Provider {
         if amount.is_err() {
             return Err(String::from("Invalid gas value"));
         }
-        let txn = TransactionRequest::new()
-            .from(signer.address())
-            .to(to)
-            .value(amount.unwrap())
-            .data(data);
-        let result = signer.send_transaction(txn, None).await;
+        let txn: TypedTransaction = match FeeOracle::get_fees(chain).await {
+            Ok(gas_fees) => Eip1559TransactionRequest::new()
+                .from(signer.inner().address())
+                .to(to)
+                .value(amount.unwrap())
+                .data(data)
+                .max_fee_per_gas(gas_fees.max_fee_per_gas)
+                .max_priority_fee_per_gas(gas_fees.max_priority_fee_per_gas)
+                .into(),
+            Err(err) => {
+                warn!("Falling back to legacy gas pricing: {}", err);
+                TransactionRequest::new()
+                    .from(signer.inner().address())
+                    .to(to)
+                    .value(amount.unwrap())
+                    .data(data)
+                    .into()
+            }
+        };
+        // count pending transactions, so a send that fails and resyncs the nonce does not reuse
+        // the nonce of a transaction that is still waiting to be mined
+        let block = Some(BlockNumber::Pending.into());
+        if let Err(err) = signer.initialize_nonce(block).await {
+            error!("Get relayer nonce failed: {}", err);
+            return Err(String::from("Failed to get relayer nonce"));
+        }
+        let result = signer.send_transaction(txn, block).await;
         return match result {
             Ok(transaction) => Ok(format!("{:?}", transaction.tx_hash())),
-            Err(error) => match error {
+            Err(NonceManagerError::MiddlewareError(error)) => match error {
                 SignerMiddlewareError::SignerError(err) => {
                     error!("Signature Error: {}", err);
                     Err(String::from("Invalid signature"))
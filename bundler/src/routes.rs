@@ -1,11 +1,17 @@
 use actix_web::web;
 use actix_web::web::ServiceConfig;
 
-use crate::handlers::admin::{add_currency_metadata, admin_get_balance, topup_paymaster_deposit};
+use crate::handlers::admin::{
+    add_call_target, add_currency_metadata, admin_get_balance, get_audit_log, get_call_targets,
+    get_paymaster_deposit_info, remove_call_target, stake_paymaster, topup_paymaster_deposit,
+    unlock_paymaster_stake, withdraw_paymaster_deposit, withdraw_paymaster_stake,
+};
 use crate::handlers::hello_world::hello_world;
 use crate::handlers::metadata::get_metadata;
+use crate::handlers::rpc::rpc;
 use crate::handlers::wallet::{
-    get_address, get_balance, list_transactions, poll_transaction, transfer,
+    batch_transfer, call_contract, get_address, get_balance, list_transactions, poll_transaction,
+    transfer,
 };
 use crate::CONFIG;
 
@@ -19,6 +25,8 @@ pub fn routes(cfg: &mut ServiceConfig) {
                         .route("balance", web::get().to(get_balance))
                         .route("transact", web::post().to(transfer))
                         .route("transfer", web::post().to(transfer))
+                        .route("transfer/batch", web::post().to(batch_transfer))
+                        .route("call", web::post().to(call_contract))
                         .route("transactions", web::get().to(list_transactions))
                         .route("transaction", web::get().to(poll_transaction)),
                 )
@@ -28,11 +36,33 @@ pub fn routes(cfg: &mut ServiceConfig) {
                             "deposit/{paymaster}",
                             web::post().to(topup_paymaster_deposit),
                         ) // the paymaster name
+                        .route(
+                            "deposit/{paymaster}",
+                            web::get().to(get_paymaster_deposit_info),
+                        )
+                        .route(
+                            "deposit/{paymaster}/withdraw",
+                            web::post().to(withdraw_paymaster_deposit),
+                        )
+                        .route("stake/{paymaster}", web::post().to(stake_paymaster))
+                        .route(
+                            "stake/{paymaster}/unlock",
+                            web::post().to(unlock_paymaster_stake),
+                        )
+                        .route(
+                            "stake/{paymaster}/withdraw",
+                            web::post().to(withdraw_paymaster_stake),
+                        )
                         .route("balance/{entity}", web::get().to(admin_get_balance))
-                        .route("metadata", web::post().to(add_currency_metadata)),
+                        .route("metadata", web::post().to(add_currency_metadata))
+                        .route("call_targets", web::get().to(get_call_targets))
+                        .route("call_targets", web::post().to(add_call_target))
+                        .route("call_targets", web::delete().to(remove_call_target))
+                        .route("audit_log", web::get().to(get_audit_log)),
                 ) // entity can be a paymaster or the EOA
                 .route("hello", web::get().to(hello_world))
-                .route("metadata", web::get().to(get_metadata)),
+                .route("metadata", web::get().to(get_metadata))
+                .route("rpc", web::post().to(rpc)),
         ),
     );
 }
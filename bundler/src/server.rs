@@ -1,36 +1,61 @@
 use std::sync::Arc;
 
 use actix_web::middleware::Logger;
+use actix_web::rt::spawn;
 use actix_web::web::Data;
 use actix_web::{App, HttpServer};
 use dotenvy::dotenv;
 use env_logger::{init_from_env, Env};
-use ethers::middleware::SignerMiddleware;
+use ethers::middleware::{NonceManagerMiddleware, SignerMiddleware};
 use ethers::types::Address;
-use ethers_signers::{LocalWallet, Signer};
 use log::info;
 use sqlx::{Pool, Postgres};
+use tokio::sync::Notify;
 
 use crate::bundler::bundler::Bundler;
+use crate::bundler::deposit_monitor::DepositMonitor;
+use crate::bundler::gas_estimator::GasEstimator;
+use crate::bundler::mempool::Mempool;
+use crate::bundler::receipt_tracker::ReceiptTracker;
 use crate::contracts::entrypoint_provider::EntryPointProvider;
+use crate::contracts::erc20_provider::Erc20Provider;
+use crate::contracts::erc20_provider_registry::Erc20ProviderRegistry;
 use crate::contracts::simple_account_factory_provider::SimpleAccountFactoryProvider;
 use crate::contracts::simple_account_provider::SimpleAccountProvider;
-use crate::contracts::usdc_provider::USDCProvider;
+use crate::contracts::token_paymaster_provider::TokenPaymasterProvider;
 use crate::db::connection::DatabaseConnection;
+use crate::db::dao::admin_audit_dao::AdminAuditDao;
+use crate::db::dao::call_target_dao::CallTargetDao;
+use crate::db::dao::idempotency_key_dao::IdempotencyKeyDao;
+use crate::db::dao::paymaster_top_up_dao::PaymasterTopUpDao;
+use crate::db::dao::rate_limit_dao::RateLimitDao;
+use crate::db::dao::sponsorship_dao::SponsorshipDao;
 use crate::db::dao::token_metadata_dao::TokenMetadataDao;
 use crate::db::dao::transaction_dao::TransactionDao;
+use crate::db::dao::user_operation_dao::UserOperationDao;
 use crate::db::dao::wallet_dao::WalletDao;
+use crate::middleware::authentication::Authentication;
+use crate::middleware::rate_limit::RateLimit;
 use crate::models::config::server::Server;
+use crate::provider::authenticator::Authenticator;
+use crate::provider::chain_registry::{ChainContext, ChainRegistry};
+use crate::provider::key_manager::KeyManager;
 use crate::provider::paymaster_provider::PaymasterProvider;
+use crate::provider::paymaster_sponsor::PaymasterSponsor;
+use crate::provider::rate_limiter::RateLimiter;
+use crate::provider::sponsorship_policy::SponsorshipPolicy;
 use crate::provider::verifying_paymaster_helper::get_verifying_paymaster_abi;
 use crate::routes::routes;
 use crate::services::admin_service::AdminService;
 use crate::services::balance_service::BalanceService;
 use crate::services::hello_world_service::HelloWorldService;
+use crate::services::rpc_service::RpcService;
 use crate::services::token_metadata_service::TokenMetadataService;
 use crate::services::transfer_service::TransferService;
 use crate::services::wallet_service::WalletService;
-use crate::{CONFIG, PROVIDER};
+use crate::signer::middleware_signer::MiddlewareSigner;
+use crate::signer::{init_signer, Signer};
+use crate::{CONFIG, PROVIDERS};
 
 #[derive(Clone)]
 pub struct ToadService {
@@ -40,106 +65,112 @@ pub struct ToadService {
     pub transfer_service: TransferService,
     pub admin_service: AdminService,
     pub token_metadata_service: TokenMetadataService,
+    pub rpc_service: RpcService,
+    pub authenticator: Authenticator,
+    pub rate_limiter: RateLimiter,
     pub db_pool: Pool<Postgres>,
 }
 
 pub async fn init_services() -> ToadService {
     init_logging();
     info!("Starting server...");
-    // contract providers
-    let client = Arc::new(PROVIDER.clone());
-    let simple_account_factory =
-        SimpleAccountFactoryProvider::init_abi(&CONFIG.run_config.current_chain, client.clone());
-    let erc20 = USDCProvider::init_abi(&CONFIG.run_config.current_chain, client.clone());
-    let entrypoint = EntryPointProvider::init_abi(&CONFIG.run_config.current_chain, client.clone());
-    let simple_account = SimpleAccountProvider::init_abi(client.clone(), Address::zero());
-    let verifying_paymaster_provider =
-        get_verifying_paymaster_abi(&CONFIG.run_config.current_chain, client.clone());
-
     //wallets
-    let verifying_paymaster_wallet: LocalWallet = std::env::var("VERIFYING_PAYMASTER_PRIVATE_KEY")
-        .expect("VERIFYING_PAYMASTER_PRIVATE_KEY must be set")
-        .parse::<LocalWallet>()
-        .unwrap();
-    let relayer_wallet: LocalWallet = std::env::var("WALLET_PRIVATE_KEY")
-        .expect("WALLET_PRIVATE_KEY must be set")
-        .parse::<LocalWallet>()
-        .unwrap();
+    let key_manager = KeyManager::init();
 
     //signers
-    let relayer_signer = SignerMiddleware::new(
-        client.clone(),
-        relayer_wallet
-            .clone()
-            .with_chain_id(CONFIG.get_chain().chain_id),
-    );
-    let bundler_signer = SignerMiddleware::new(
-        client.clone(),
-        relayer_wallet
-            .clone()
-            .with_chain_id(CONFIG.get_chain().chain_id),
-    );
+    let verifying_paymaster_signer = init_signer(&CONFIG.signers.verifying_paymaster);
+    let relayer = init_signer(&CONFIG.signers.relayer);
 
     //daos
     let pool = DatabaseConnection::init().await;
     let wallet_dao = WalletDao { pool: pool.clone() };
     let transaction_dao = TransactionDao { pool: pool.clone() };
     let token_metadata_dao = TokenMetadataDao { pool: pool.clone() };
+    let user_operation_dao = UserOperationDao { pool: pool.clone() };
+    let call_target_dao = CallTargetDao { pool: pool.clone() };
+    let sponsorship_dao = SponsorshipDao { pool: pool.clone() };
+    let paymaster_top_up_dao = PaymasterTopUpDao { pool: pool.clone() };
+    let admin_audit_dao = AdminAuditDao { pool: pool.clone() };
+    let rate_limit_dao = RateLimitDao { pool: pool.clone() };
+    let idempotency_key_dao = IdempotencyKeyDao { pool: pool.clone() };
+    wallet_dao
+        .assign_chain(CONFIG.run_config.current_chain.clone())
+        .await;
+    user_operation_dao
+        .assign_chain(CONFIG.run_config.current_chain.clone())
+        .await;
 
-    // providers
-    let verify_paymaster_provider = PaymasterProvider {
-        provider: verifying_paymaster_provider.clone(),
-    };
-    let entrypoint_provider = EntryPointProvider {
-        abi: entrypoint.clone(),
-    };
-    let bundler = Bundler {
-        signer: bundler_signer.clone(),
-        entrypoint: entrypoint_provider.clone(),
-    };
-    let usdc_provider = USDCProvider { abi: erc20.clone() };
-    let simple_account_provider = SimpleAccountProvider {
-        abi: simple_account.clone(),
-    };
-    let simple_account_factory_provider = SimpleAccountFactoryProvider {
-        abi: simple_account_factory.clone(),
+    // chains
+    let chain_registry = ChainRegistry::new(
+        CONFIG
+            .chains
+            .keys()
+            .map(|chain| {
+                init_chain(
+                    chain,
+                    relayer.clone(),
+                    user_operation_dao.clone(),
+                    transaction_dao.clone(),
+                    wallet_dao.clone(),
+                    paymaster_top_up_dao.clone(),
+                )
+            })
+            .collect(),
+    );
+    let default_chain = chain_registry.get_default();
+    let paymaster_sponsor = PaymasterSponsor {
+        signer: verifying_paymaster_signer.clone(),
+        sponsorship_policy: SponsorshipPolicy {
+            sponsorship_dao: sponsorship_dao.clone(),
+        },
     };
 
+    let rate_limiter = RateLimiter::init(rate_limit_dao);
+
     // Services
     let hello_world_service = HelloWorldService {};
     let wallet_service = WalletService {
         wallet_dao: wallet_dao.clone(),
         transaction_dao: transaction_dao.clone(),
-        simple_account_factory_provider: simple_account_factory.clone(),
-        client: client.clone(),
+        chain_registry: chain_registry.clone(),
+        key_manager: key_manager.clone(),
     };
     let balance_service = BalanceService {
         wallet_dao: wallet_dao.clone(),
         token_metadata_dao: token_metadata_dao.clone(),
-        erc20_provider: erc20.clone(),
+        chain_registry: chain_registry.clone(),
     };
     let transfer_service = TransferService {
         wallet_dao: wallet_dao.clone(),
         transaction_dao: transaction_dao.clone(),
         token_metadata_dao: token_metadata_dao.clone(),
-        usdc_provider,
-        entrypoint_provider: entrypoint_provider.clone(),
-        simple_account_provider: simple_account_provider.clone(),
-        simple_account_factory_provider: simple_account_factory_provider.clone(),
-        verifying_paymaster_provider: verify_paymaster_provider.clone(),
-        verifying_paymaster_wallet: verifying_paymaster_wallet.clone(),
-        scw_owner_wallet: relayer_wallet.clone(),
-        bundler: bundler.clone(),
+        call_target_dao: call_target_dao.clone(),
+        chain_registry: chain_registry.clone(),
+        verifying_paymaster_signer: verifying_paymaster_signer.clone(),
+        scw_owner_signer: relayer.clone(),
+        key_manager: key_manager.clone(),
+        paymaster_sponsor: paymaster_sponsor.clone(),
+        rate_limiter: rate_limiter.clone(),
+        idempotency_key_dao: idempotency_key_dao.clone(),
     };
     let admin_service = AdminService {
-        paymaster_provider: verify_paymaster_provider.clone(),
-        entrypoint_provider: entrypoint_provider.clone(),
-        relayer_signer: relayer_signer.clone(),
+        chain_registry: chain_registry.clone(),
         metadata_dao: token_metadata_dao.clone(),
+        call_target_dao: call_target_dao.clone(),
+        admin_audit_dao: admin_audit_dao.clone(),
     };
     let token_metadata_service = TokenMetadataService {
         token_metadata_dao: token_metadata_dao.clone(),
     };
+    let rpc_service = RpcService {
+        chain: default_chain.chain.clone(),
+        entrypoint_provider: default_chain.entrypoint_provider.clone(),
+        bundler: default_chain.bundler.clone(),
+        gas_estimator: default_chain.gas_estimator.clone(),
+        verifying_paymaster_provider: default_chain.verifying_paymaster_provider.clone(),
+        paymaster_sponsor: paymaster_sponsor.clone(),
+        token_metadata_dao: token_metadata_dao.clone(),
+    };
 
     ToadService {
         hello_world_service,
@@ -148,11 +179,109 @@ pub async fn init_services() -> ToadService {
         transfer_service,
         admin_service,
         token_metadata_service,
+        rpc_service,
+        authenticator: Authenticator::init(),
+        rate_limiter,
         db_pool: pool,
     }
 }
 
-fn init_logging() {
+// Contract providers, bundler, receipt tracker and deposit monitor for a single chain
+fn init_chain(
+    chain: &String,
+    relayer: Arc<dyn Signer>,
+    user_operation_dao: UserOperationDao,
+    transaction_dao: TransactionDao,
+    wallet_dao: WalletDao,
+    paymaster_top_up_dao: PaymasterTopUpDao,
+) -> ChainContext {
+    // contract providers
+    let client = Arc::new(PROVIDERS[chain].clone());
+    let simple_account_factory = SimpleAccountFactoryProvider::init_abi(chain, client.clone());
+    let erc20 = Erc20Provider::init_abi(chain, client.clone());
+    let entrypoint = EntryPointProvider::init_abi(chain, client.clone());
+    let simple_account = SimpleAccountProvider::init_abi(client.clone(), Address::zero());
+    let verifying_paymaster_provider = get_verifying_paymaster_abi(chain, client.clone());
+
+    //signers
+    let relayer_middleware = SignerMiddleware::new(
+        client.clone(),
+        MiddlewareSigner::new(relayer.clone(), CONFIG.chains[chain].chain_id),
+    );
+    let relayer_address = relayer_middleware.address();
+    let relayer_signer = Arc::new(NonceManagerMiddleware::new(
+        relayer_middleware,
+        relayer_address,
+    ));
+
+    // providers
+    let verify_paymaster_provider = PaymasterProvider {
+        provider: verifying_paymaster_provider.clone(),
+    };
+    let entrypoint_provider = EntryPointProvider {
+        abi: entrypoint.clone(),
+    };
+    let erc20_provider = Erc20Provider { abi: erc20.clone() };
+    let erc20_provider_registry = Erc20ProviderRegistry::new(client.clone());
+    let token_paymaster_providers = TokenPaymasterProvider::init_providers(chain, client.clone());
+    let simple_account_provider = SimpleAccountProvider {
+        abi: simple_account.clone(),
+    };
+    let mempool = Mempool::new(chain.clone(), user_operation_dao.clone());
+    let receipt_tracker = ReceiptTracker {
+        chain: chain.clone(),
+        entrypoint: entrypoint_provider.clone(),
+        user_operation_dao: user_operation_dao.clone(),
+        transaction_dao: transaction_dao.clone(),
+        simple_account_provider: simple_account_provider.clone(),
+        erc20_provider: erc20_provider.clone(),
+        token_paymaster_providers: token_paymaster_providers.clone(),
+        wallet_dao,
+        mempool: mempool.clone(),
+        notify: Arc::new(Notify::new()),
+    };
+    spawn(receipt_tracker.clone().run());
+    let bundler = Bundler {
+        chain: chain.clone(),
+        signer: relayer_signer.clone(),
+        entrypoint: entrypoint_provider.clone(),
+        mempool,
+        transaction_dao: transaction_dao.clone(),
+        receipt_tracker,
+    };
+    spawn(bundler.clone().run());
+    let gas_estimator = GasEstimator {
+        chain: chain.clone(),
+        entrypoint: entrypoint_provider.clone(),
+    };
+    let simple_account_factory_provider = SimpleAccountFactoryProvider {
+        abi: simple_account_factory.clone(),
+    };
+    let deposit_monitor = DepositMonitor::new(
+        chain.clone(),
+        entrypoint_provider.clone(),
+        verify_paymaster_provider.clone(),
+        relayer_signer.clone(),
+        paymaster_top_up_dao,
+    );
+    spawn(deposit_monitor.run());
+
+    ChainContext {
+        chain: chain.clone(),
+        client,
+        entrypoint_provider,
+        simple_account_factory_provider,
+        simple_account_provider,
+        verifying_paymaster_provider: verify_paymaster_provider,
+        erc20_provider_registry,
+        token_paymaster_providers,
+        relayer_signer,
+        bundler,
+        gas_estimator,
+    }
+}
+
+pub fn init_logging() {
     let log_level = CONFIG.log.level.as_str();
     std::env::set_var("RUST_LOG", log_level);
     init_from_env(Env::default().default_filter_or(log_level));
@@ -163,6 +292,12 @@ pub async fn run(service: ToadService, server: Server) -> std::io::Result<()> {
 
     HttpServer::new(move || {
         App::new()
+            .wrap(RateLimit {
+                rate_limiter: service.rate_limiter.clone(),
+            })
+            .wrap(Authentication {
+                authenticator: service.authenticator.clone(),
+            })
             .wrap(Logger::default())
             .configure(routes)
             .app_data(Data::new(service.hello_world_service.clone()))
@@ -171,6 +306,7 @@ pub async fn run(service: ToadService, server: Server) -> std::io::Result<()> {
             .app_data(Data::new(service.transfer_service.clone()))
             .app_data(Data::new(service.admin_service.clone()))
             .app_data(Data::new(service.token_metadata_service.clone()))
+            .app_data(Data::new(service.rpc_service.clone()))
             .app_data(Data::new(service.db_pool.clone()))
     })
     .bind(server.url())?
@@ -1,34 +1,113 @@
-use ethers::middleware::SignerMiddleware;
-use ethers::providers::{Http, Provider};
-use ethers::types::Address;
-use ethers_signers::LocalWallet;
-use std::sync::Arc;
+use ethers::types::{Address, Bytes, U256};
+use ethers::utils::format_ether;
+use serde::Serialize;
+use serde_json::Value;
 
 use crate::constants::Constants;
-use crate::contracts::entrypoint_provider::EntryPointProvider;
+use crate::db::dao::admin_audit_dao::{AdminAuditDao, AdminAuditEntry};
+use crate::db::dao::call_target_dao::CallTargetDao;
 use crate::db::dao::token_metadata_dao::TokenMetadataDao;
 use crate::errors::ApiError;
 use crate::models::admin::add_metadata_request::AddMetadataRequest;
+use crate::models::admin::audit_log_params::AuditLogParams;
+use crate::models::admin::audit_log_response::AuditLogResponse;
+use crate::models::admin::call_target_request::CallTargetRequest;
+use crate::models::admin::call_targets_response::CallTargetsResponse;
+use crate::models::admin::deposit_info_response::DepositInfoResponse;
 use crate::models::admin::metadata_response::MetadataResponse;
+use crate::models::admin::paymaster_request::{
+    PaymasterRequest, PaymasterStakeRequest, PaymasterWithdrawRequest,
+};
+use crate::models::admin::role::Permission;
+use crate::models::currency::Currency;
 use crate::models::metadata::Metadata;
+use crate::models::principal::Principal;
 use crate::models::transfer::status::Status;
 use crate::models::transfer::transaction_response::TransactionResponse;
 use crate::models::transfer::transfer_response::TransferResponse;
 use crate::models::wallet::balance_request::Balance;
 use crate::models::wallet::balance_response::BalanceResponse;
-use crate::provider::paymaster_provider::PaymasterProvider;
+use crate::provider::chain_registry::{ChainContext, ChainRegistry};
 use crate::provider::web3_provider::Web3Provider;
 use crate::CONFIG;
 
 #[derive(Clone)]
 pub struct AdminService {
-    pub paymaster_provider: PaymasterProvider,
-    pub entrypoint_provider: EntryPointProvider,
-    pub relayer_signer: SignerMiddleware<Arc<Provider<Http>>, LocalWallet>,
+    pub chain_registry: ChainRegistry,
     pub metadata_dao: TokenMetadataDao,
+    pub call_target_dao: CallTargetDao,
+    pub admin_audit_dao: AdminAuditDao,
 }
 
 impl AdminService {
+    // denied calls are audited too
+    pub async fn authorize(
+        &self,
+        principal: &Principal,
+        permission: Permission,
+        action: &str,
+        parameters: &Value,
+    ) -> Result<(), ApiError> {
+        if principal.has_permission(permission) {
+            return Ok(());
+        }
+        let response: Result<(), ApiError> = Err(ApiError::Forbidden(format!(
+            "{:?} permission required",
+            permission
+        )));
+        self.record_action(principal, action, parameters.clone(), &response)
+            .await;
+        response
+    }
+
+    pub async fn record_action<T: Serialize>(
+        &self,
+        principal: &Principal,
+        action: &str,
+        parameters: Value,
+        response: &Result<T, ApiError>,
+    ) {
+        let mut entry = AdminAuditEntry {
+            actor: principal.user.clone(),
+            client: principal.client.clone(),
+            action: action.to_string(),
+            parameters,
+            ..Default::default()
+        };
+        match response {
+            Ok(data) => {
+                entry.result = String::from("success");
+                // calls sent on chain respond with their transaction
+                entry.transaction_hash = serde_json::to_value(data).ok().and_then(|data| {
+                    data.pointer("/transaction/transaction_hash")
+                        .and_then(Value::as_str)
+                        .map(String::from)
+                });
+            }
+            Err(err) => {
+                entry.result = String::from("failure");
+                entry.error = Some(err.to_string());
+            }
+        }
+        self.admin_audit_dao.create_entry(entry).await;
+    }
+
+    pub async fn get_audit_log(
+        &self,
+        params: AuditLogParams,
+    ) -> Result<Vec<AuditLogResponse>, ApiError> {
+        let entries = self
+            .admin_audit_dao
+            .list_entries(
+                params.page_size.unwrap_or(10),
+                params.id.unwrap_or(i32::MAX),
+                params.actor,
+                params.action,
+            )
+            .await;
+        Ok(entries.into_iter().map(AuditLogResponse::from).collect())
+    }
+
     pub async fn topup_paymaster_deposit(
         &self,
         value: String,
@@ -38,31 +117,148 @@ impl AdminService {
         if metadata.currency != Constants::NATIVE {
             return Err(ApiError::BadRequest("Invalid currency".to_string()));
         }
-        if paymaster != Constants::VERIFYING_PAYMASTER {
-            return Err(ApiError::BadRequest("Invalid Paymaster".to_string()));
-        }
+        Self::validate_paymaster(&paymaster)?;
+        let context = self.chain_registry.get(&metadata.chain)?;
+        let chain = &CONFIG.chains[&context.chain];
 
-        let data = self
+        let data = context
             .entrypoint_provider
-            .add_deposit(CONFIG.get_chain().verifying_paymaster_address)
+            .add_deposit(chain.verifying_paymaster_address)
             .await;
         if data.is_err() {
             return Err(ApiError::BadRequest(String::from("failed to topup")));
         }
         let response = Web3Provider::execute(
-            self.relayer_signer.clone(),
-            CONFIG.get_chain().entrypoint_address,
+            context.relayer_signer.clone(),
+            &context.chain,
+            chain.entrypoint_address,
+            value,
+            data.unwrap(),
+            context.entrypoint_provider.abi(),
+        )
+        .await;
+        Self::get_transfer_response(&context.chain, response)
+    }
+
+    pub async fn stake_paymaster(
+        &self,
+        paymaster: String,
+        request: PaymasterStakeRequest,
+    ) -> Result<TransferResponse, ApiError> {
+        Self::validate_paymaster(&paymaster)?;
+        let context = self.chain_registry.get(&request.get_chain())?;
+        let data = context
+            .verifying_paymaster_provider
+            .add_stake(request.unstake_delay_sec);
+        self.execute_paymaster_call(context, request.get_value(), data)
+            .await
+    }
+
+    pub async fn unlock_paymaster_stake(
+        &self,
+        paymaster: String,
+        request: PaymasterRequest,
+    ) -> Result<TransferResponse, ApiError> {
+        Self::validate_paymaster(&paymaster)?;
+        let context = self.chain_registry.get(&request.get_chain())?;
+        let data = context.verifying_paymaster_provider.unlock_stake();
+        self.execute_paymaster_call(context, 0.to_string(), data)
+            .await
+    }
+
+    // the stake can only be withdrawn once the unstake delay has passed since unlocking
+    pub async fn withdraw_paymaster_stake(
+        &self,
+        paymaster: String,
+        request: PaymasterWithdrawRequest,
+    ) -> Result<TransferResponse, ApiError> {
+        Self::validate_paymaster(&paymaster)?;
+        let context = self.chain_registry.get(&request.get_chain())?;
+        let withdraw_address = Self::get_withdraw_address(&request)?;
+        let data = context
+            .verifying_paymaster_provider
+            .withdraw_stake(withdraw_address);
+        self.execute_paymaster_call(context, 0.to_string(), data)
+            .await
+    }
+
+    pub async fn withdraw_paymaster_deposit(
+        &self,
+        paymaster: String,
+        request: PaymasterWithdrawRequest,
+    ) -> Result<TransferResponse, ApiError> {
+        Self::validate_paymaster(&paymaster)?;
+        let context = self.chain_registry.get(&request.get_chain())?;
+        let withdraw_address = Self::get_withdraw_address(&request)?;
+        let amount = match U256::from_dec_str(&request.get_value()) {
+            Ok(amount) => amount,
+            Err(_) => return Err(ApiError::BadRequest("Invalid value".to_string())),
+        };
+        let data = context
+            .verifying_paymaster_provider
+            .withdraw_to(withdraw_address, amount);
+        self.execute_paymaster_call(context, 0.to_string(), data)
+            .await
+    }
+
+    pub async fn get_paymaster_deposit_info(
+        &self,
+        paymaster: String,
+        request: PaymasterRequest,
+    ) -> Result<DepositInfoResponse, ApiError> {
+        Self::validate_paymaster(&paymaster)?;
+        let context = self.chain_registry.get(&request.get_chain())?;
+        let chain = &CONFIG.chains[&context.chain];
+        let deposit_info = context
+            .entrypoint_provider
+            .get_deposit_info(chain.verifying_paymaster_address)
+            .await;
+        match deposit_info {
+            Ok(deposit_info) => Ok(DepositInfoResponse {
+                paymaster: format!("{:?}", chain.verifying_paymaster_address),
+                deposit: format_ether(deposit_info.deposit),
+                staked: deposit_info.staked,
+                stake: format_ether(deposit_info.stake),
+                unstake_delay_sec: deposit_info.unstake_delay_sec,
+                withdraw_time: deposit_info.withdraw_time,
+                currency: chain.currency.clone(),
+            }),
+            Err(err) => Err(ApiError::InternalServer(err)),
+        }
+    }
+
+    // stake and withdrawals are owner only calls the relayer makes on the paymaster contract
+    async fn execute_paymaster_call(
+        &self,
+        context: &ChainContext,
+        value: String,
+        data: Result<Bytes, String>,
+    ) -> Result<TransferResponse, ApiError> {
+        if data.is_err() {
+            return Err(ApiError::InternalServer(data.err().unwrap()));
+        }
+        let response = Web3Provider::execute(
+            context.relayer_signer.clone(),
+            &context.chain,
+            CONFIG.chains[&context.chain].verifying_paymaster_address,
             value,
             data.unwrap(),
-            self.entrypoint_provider.abi(),
+            context.verifying_paymaster_provider.abi(),
         )
         .await;
+        Self::get_transfer_response(&context.chain, response)
+    }
+
+    fn get_transfer_response(
+        chain: &str,
+        response: Result<String, String>,
+    ) -> Result<TransferResponse, ApiError> {
         match response {
             Ok(txn_hash) => Ok(TransferResponse {
                 transaction: TransactionResponse::new(
                     txn_hash.clone(),
                     Status::PENDING,
-                    CONFIG.get_chain().explorer_url.clone() + &txn_hash.clone(),
+                    CONFIG.chains[chain].explorer_url.clone() + &txn_hash.clone(),
                 ),
                 transaction_id: "".to_string(),
             }),
@@ -70,6 +266,20 @@ impl AdminService {
         }
     }
 
+    fn get_withdraw_address(request: &PaymasterWithdrawRequest) -> Result<Address, ApiError> {
+        match request.get_withdraw_address().parse() {
+            Ok(withdraw_address) => Ok(withdraw_address),
+            Err(_) => Err(ApiError::BadRequest("Invalid withdraw address".to_string())),
+        }
+    }
+
+    fn validate_paymaster(paymaster: &str) -> Result<(), ApiError> {
+        if paymaster != Constants::VERIFYING_PAYMASTER {
+            return Err(ApiError::BadRequest("Invalid Paymaster".to_string()));
+        }
+        Ok(())
+    }
+
     pub async fn get_balance(
         &self,
         entity: String,
@@ -78,14 +288,19 @@ impl AdminService {
         if data.currency != Constants::NATIVE {
             return Err(ApiError::BadRequest("Invalid currency".to_string()));
         }
+        let context = self.chain_registry.get(&data.chain)?;
         if Constants::PAYMASTER == entity {
-            let paymaster_address = &CONFIG.get_chain().verifying_paymaster_address;
-            let response = self.paymaster_provider.get_deposit().await;
+            let paymaster_address = &CONFIG.chains[&context.chain].verifying_paymaster_address;
+            let response = context
+                .verifying_paymaster_provider
+                .get_deposit()
+                .await
+                .map(format_ether);
             return Self::get_balance_response(paymaster_address, response, data.currency);
         }
         if Constants::RELAYER == entity {
             let relayer_address = &CONFIG.run_config.account_owner;
-            let response = Web3Provider::get_balance(relayer_address.clone()).await;
+            let response = Web3Provider::get_balance(&context.chain, relayer_address.clone()).await;
             return Self::get_balance_response(relayer_address, response, data.currency);
         }
         Err(ApiError::BadRequest("Invalid entity".to_string()))
@@ -111,6 +326,13 @@ impl AdminService {
         &self,
         metadata: AddMetadataRequest,
     ) -> Result<MetadataResponse, ApiError> {
+        if let Some(Currency::Erc20) = Currency::from_str(metadata.get_token_type()) {
+            if metadata.get_contract_address().parse::<Address>().is_err() {
+                return Err(ApiError::BadRequest(
+                    "Invalid token contract address".to_string(),
+                ));
+            }
+        }
         self.metadata_dao
             .add_metadata(
                 metadata.get_chain().clone(),
@@ -136,4 +358,45 @@ impl AdminService {
 
         Ok(exponent_metadata)
     }
+
+    pub async fn add_call_target(
+        &self,
+        call_target: CallTargetRequest,
+    ) -> Result<CallTargetsResponse, ApiError> {
+        let context = self.chain_registry.get(&call_target.get_chain())?;
+        if call_target.get_address().parse::<Address>().is_err() {
+            return Err(ApiError::BadRequest("Invalid target address".to_string()));
+        }
+        self.call_target_dao
+            .add_call_target(
+                context.chain.clone(),
+                call_target.get_address(),
+                call_target.get_name(),
+            )
+            .await;
+        self.get_call_targets(context.chain.clone()).await
+    }
+
+    pub async fn remove_call_target(
+        &self,
+        call_target: CallTargetRequest,
+    ) -> Result<CallTargetsResponse, ApiError> {
+        let context = self.chain_registry.get(&call_target.get_chain())?;
+        self.call_target_dao
+            .remove_call_target(context.chain.clone(), call_target.get_address())
+            .await;
+        self.get_call_targets(context.chain.clone()).await
+    }
+
+    pub async fn get_call_targets(&self, chain: String) -> Result<CallTargetsResponse, ApiError> {
+        let context = self.chain_registry.get(&chain)?;
+        let call_targets = self
+            .call_target_dao
+            .get_call_targets(context.chain.clone())
+            .await;
+        Ok(CallTargetsResponse::new(
+            context.chain.clone(),
+            call_targets,
+        ))
+    }
 }
@@ -1,55 +1,67 @@
 use ethers::abi::Address;
-use ethers::providers::{Http, Middleware, Provider};
-use log::info;
+use ethers::providers::Middleware;
 
-use crate::contracts::usdc_provider::ERC20;
 use crate::db::dao::token_metadata_dao::TokenMetadataDao;
 use crate::db::dao::wallet_dao::WalletDao;
 use crate::errors::ApiError;
 use crate::models::currency::Currency;
 use crate::models::wallet::balance_response::BalanceResponse;
-use crate::PROVIDER;
+use crate::provider::chain_registry::ChainRegistry;
 
 #[derive(Clone)]
 pub struct BalanceService {
     pub wallet_dao: WalletDao,
     pub token_metadata_dao: TokenMetadataDao,
-    pub erc20_provider: ERC20<Provider<Http>>,
+    pub chain_registry: ChainRegistry,
 }
 
 impl BalanceService {
     pub async fn get_wallet_balance(
         &self,
-        chain: &String,
+        chain: &str,
         currency: &String,
         user: &str,
     ) -> Result<BalanceResponse, ApiError> {
-        info!("Chain: {:?}", chain); // will be relevant when we add support for multiple chains
+        let context = self.chain_registry.get(chain)?;
         let balance: String;
-        let address = self.wallet_dao.get_wallet_address(user.to_string()).await;
+        let address = self
+            .wallet_dao
+            .get_wallet_address(user.to_string(), context.chain.clone())
+            .await;
         if address.is_empty() {
             return Err(ApiError::NotFound("Wallet not found".to_string()));
         }
         let user: Address = address.parse().unwrap();
 
-        match Currency::from_str(
-            self.token_metadata_dao
-                .get_metadata_for_chain(chain.clone(), Some(currency.to_string()))
-                .await[0]
-                .token_type
-                .clone(),
-        ) {
+        let metadata = self
+            .token_metadata_dao
+            .get_metadata_for_chain(context.chain.clone(), Some(currency.to_string()))
+            .await;
+        if metadata.is_empty() {
+            return Err(ApiError::BadRequest("Currency not supported".to_string()));
+        }
+        let metadata = metadata[0].clone();
+
+        match Currency::from_str(metadata.token_type.clone()) {
             None => return Err(ApiError::BadRequest("Currency not supported".to_string())),
             Some(Currency::Erc20) => {
-                balance = self
-                    .erc20_provider
+                let erc20_provider = context
+                    .erc20_provider_registry
+                    .get(&metadata.contract_address);
+                if erc20_provider.is_err() {
+                    return Err(ApiError::InternalServer(erc20_provider.err().unwrap()));
+                }
+                balance = erc20_provider
+                    .unwrap()
+                    .abi
                     .balance_of(user.clone())
                     .await
                     .unwrap()
                     .to_string();
             }
             Some(Currency::Native) => {
-                balance = PROVIDER
+                balance = context
+                    .client
                     .get_balance(user.clone(), None)
                     .await
                     .unwrap()
@@ -61,11 +73,7 @@ impl BalanceService {
             balance: balance.clone(),
             address: address.clone(),
             currency: currency.to_string(),
-            exponent: self
-                .token_metadata_dao
-                .get_metadata_for_chain(chain.clone(), Some(currency.clone()))
-                .await[0]
-                .exponent,
+            exponent: metadata.exponent,
         })
     }
 }
@@ -1,6 +1,7 @@
 pub mod admin_service;
 pub mod balance_service;
 pub mod hello_world_service;
+pub mod rpc_service;
 pub mod token_metadata_service;
 pub mod transfer_service;
 pub mod wallet_service;
@@ -0,0 +1,304 @@
+use ethers::contract::EthEvent;
+use ethers::providers::Middleware;
+use ethers::types::{Address, H256, U256};
+use log::{error, info};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::bundler::bundler::Bundler;
+use crate::bundler::gas_estimator::GasEstimator;
+use crate::contracts::entrypoint_provider::{EntryPointProvider, UserOperationEventFilter};
+use crate::db::dao::token_metadata_dao::TokenMetadataDao;
+use crate::models::admin::role::Permission;
+use crate::models::contract_interaction::user_operation::UserOperation;
+use crate::models::contract_interaction::user_operation_error::UserOperationError;
+use crate::models::principal::Principal;
+use crate::models::rpc::json_rpc_request::JsonRpcRequest;
+use crate::models::rpc::json_rpc_response::{JsonRpcError, JsonRpcResponse};
+use crate::models::rpc::sponsor_user_operation_response::SponsorUserOperationResponse;
+use crate::models::rpc::user_operation_receipt::UserOperationReceipt;
+use crate::models::rpc::user_operation_response::UserOperationResponse;
+use crate::provider::fee_oracle::FeeOracle;
+use crate::provider::paymaster_provider::PaymasterProvider;
+use crate::provider::paymaster_sponsor::{PaymasterSponsor, SponsorshipError};
+use crate::provider::sponsorship_policy::{SponsorshipPolicy, SponsorshipScope};
+use crate::{CONFIG, PROVIDERS};
+
+#[derive(Clone)]
+pub struct RpcService {
+    pub chain: String,
+    pub entrypoint_provider: EntryPointProvider,
+    pub bundler: Bundler,
+    pub gas_estimator: GasEstimator,
+    pub verifying_paymaster_provider: PaymasterProvider,
+    pub paymaster_sponsor: PaymasterSponsor,
+    pub token_metadata_dao: TokenMetadataDao,
+}
+
+impl RpcService {
+    pub async fn handle(
+        &self,
+        request: JsonRpcRequest,
+        principal: Option<&Principal>,
+    ) -> JsonRpcResponse {
+        let id = request.id.clone();
+        if request.jsonrpc != "2.0" {
+            return JsonRpcResponse::new(
+                id,
+                Err(JsonRpcError::invalid_request(String::from(
+                    "jsonrpc must be 2.0",
+                ))),
+            );
+        }
+        if request.method.starts_with("pm_")
+            && !principal.is_some_and(|principal| principal.has_permission(Permission::Sponsor))
+        {
+            return JsonRpcResponse::new(
+                id,
+                Err(JsonRpcError::unauthorized(format!(
+                    "{:?} permission required",
+                    Permission::Sponsor
+                ))),
+            );
+        }
+        let result = match request.method.as_str() {
+            "eth_chainId" => Self::to_value(U256::from(CONFIG.chains[&self.chain].chain_id)),
+            "eth_supportedEntryPoints" => {
+                Self::to_value(vec![CONFIG.chains[&self.chain].entrypoint_address])
+            }
+            "eth_sendUserOperation" => self.send_user_operation(&request).await,
+            "eth_estimateUserOperationGas" => self.estimate_user_operation_gas(&request).await,
+            "eth_getUserOperationByHash" => self.get_user_operation_by_hash(&request).await,
+            "eth_getUserOperationReceipt" => self.get_user_operation_receipt(&request).await,
+            "pm_sponsorUserOperation" => self.sponsor_user_operation(&request).await,
+            method => Err(JsonRpcError::method_not_found(method)),
+        };
+        JsonRpcResponse::new(id, result)
+    }
+
+    async fn send_user_operation(&self, request: &JsonRpcRequest) -> Result<Value, JsonRpcError> {
+        let user_op: UserOperation = request.param(0)?;
+        let entry_point = self.get_entry_point(request, 1)?;
+
+        let user_op_hash =
+            H256::from(user_op.hash(entry_point, CONFIG.chains[&self.chain].chain_id));
+        let result = self.bundler.add(user_op, user_op_hash, None).await;
+        match result {
+            Ok(_) => {
+                info!("User operation queued. Hash: {:?}", user_op_hash);
+                Self::to_value(user_op_hash)
+            }
+            Err(err) => Err(JsonRpcError::from(err)),
+        }
+    }
+
+    async fn estimate_user_operation_gas(
+        &self,
+        request: &JsonRpcRequest,
+    ) -> Result<Value, JsonRpcError> {
+        let user_op: UserOperation = request.param(0)?;
+        self.get_entry_point(request, 1)?;
+
+        let estimate = self.gas_estimator.estimate(&user_op).await?;
+        Self::to_value(estimate)
+    }
+
+    async fn get_user_operation_by_hash(
+        &self,
+        request: &JsonRpcRequest,
+    ) -> Result<Value, JsonRpcError> {
+        let user_op_hash: H256 = request.param(0)?;
+        let event = self
+            .entrypoint_provider
+            .get_user_operation_event(user_op_hash)
+            .await
+            .map_err(JsonRpcError::internal)?;
+        let (_, meta) = match event {
+            None => return Ok(Value::Null),
+            Some(event) => event,
+        };
+
+        let transaction = PROVIDERS[&self.chain]
+            .get_transaction(meta.transaction_hash)
+            .await
+            .map_err(|err| {
+                error!("Get transaction failed: {:?}", err);
+                JsonRpcError::internal(String::from("failed to get transaction"))
+            })?;
+        let transaction = match transaction {
+            None => return Ok(Value::Null),
+            Some(transaction) => transaction,
+        };
+
+        let entry_point = CONFIG.chains[&self.chain].entrypoint_address;
+        let user_operation = self
+            .entrypoint_provider
+            .decode_handle_ops(&transaction.input)
+            .map_err(JsonRpcError::internal)?
+            .into_iter()
+            .find(|user_op| {
+                H256::from(user_op.hash(entry_point, CONFIG.chains[&self.chain].chain_id))
+                    == user_op_hash
+            });
+        match user_operation {
+            None => Ok(Value::Null),
+            Some(user_operation) => Self::to_value(UserOperationResponse {
+                user_operation,
+                entry_point,
+                transaction_hash: meta.transaction_hash,
+                block_hash: meta.block_hash,
+                block_number: meta.block_number,
+            }),
+        }
+    }
+
+    async fn get_user_operation_receipt(
+        &self,
+        request: &JsonRpcRequest,
+    ) -> Result<Value, JsonRpcError> {
+        let user_op_hash: H256 = request.param(0)?;
+        let event = self
+            .entrypoint_provider
+            .get_user_operation_event(user_op_hash)
+            .await
+            .map_err(JsonRpcError::internal)?;
+        let (event, meta) = match event {
+            None => return Ok(Value::Null),
+            Some(event) => event,
+        };
+
+        let receipt = PROVIDERS[&self.chain]
+            .get_transaction_receipt(meta.transaction_hash)
+            .await
+            .map_err(|err| {
+                error!("Get transaction receipt failed: {:?}", err);
+                JsonRpcError::internal(String::from("failed to get transaction receipt"))
+            })?;
+        let receipt = match receipt {
+            None => return Ok(Value::Null),
+            Some(receipt) => receipt,
+        };
+
+        // logs emitted by this user operation sit between the previous UserOperationEvent and its own
+        let logs = receipt
+            .logs
+            .iter()
+            .take_while(|log| log.log_index != Some(meta.log_index))
+            .fold(vec![], |mut logs, log| {
+                if log.topics.first() == Some(&UserOperationEventFilter::signature()) {
+                    logs.clear();
+                } else {
+                    logs.push(log.clone());
+                }
+                logs
+            });
+
+        Self::to_value(UserOperationReceipt {
+            user_op_hash,
+            entry_point: CONFIG.chains[&self.chain].entrypoint_address,
+            sender: event.sender,
+            nonce: event.nonce,
+            paymaster: event.paymaster,
+            actual_gas_cost: event.actual_gas_cost,
+            actual_gas_used: event.actual_gas_used,
+            success: event.success,
+            logs,
+            receipt,
+        })
+    }
+
+    // fills in missing fees and gas limits, then signs paymasterAndData for the final values
+    async fn sponsor_user_operation(
+        &self,
+        request: &JsonRpcRequest,
+    ) -> Result<Value, JsonRpcError> {
+        let mut user_op: UserOperation = request.param(0)?;
+        let entry_point = self.get_entry_point(request, 1)?;
+        let tokens = self
+            .token_metadata_dao
+            .get_metadata_for_chain(self.chain.clone(), None)
+            .await;
+        let scope = SponsorshipScope::from_call_data(&user_op.calldata, &tokens)
+            .map_err(|reason| Self::to_json_rpc_error(SponsorshipError::Denied(reason)))?;
+
+        if user_op.max_fee_per_gas == 0 {
+            let gas_fees = FeeOracle::get_fees(&self.chain)
+                .await
+                .map_err(JsonRpcError::internal)?;
+            user_op.gas_fees(gas_fees);
+        }
+        let validity_window = SponsorshipPolicy::get_validity_window();
+        if user_op.call_gas_limit == 0
+            || user_op.verification_gas_limit == 0
+            || user_op.pre_verification_gas == 0
+        {
+            let dummy_paymaster_and_data = self
+                .paymaster_sponsor
+                .get_dummy_paymaster_and_data(&self.chain, validity_window)
+                .await
+                .map_err(Self::to_json_rpc_error)?;
+            user_op.paymaster_and_data(dummy_paymaster_and_data);
+            self.gas_estimator.fill(&mut user_op).await?;
+        }
+        let (paymaster_and_data, reservation) = self
+            .paymaster_sponsor
+            .sponsor(
+                &self.chain,
+                &self.verifying_paymaster_provider,
+                &user_op,
+                &scope,
+                validity_window,
+            )
+            .await
+            .map_err(Self::to_json_rpc_error)?;
+        user_op.paymaster_and_data(paymaster_and_data);
+
+        let user_op_hash =
+            H256::from(user_op.hash(entry_point, CONFIG.chains[&self.chain].chain_id));
+        self.paymaster_sponsor
+            .sponsorship_policy
+            .record(reservation, user_op_hash)
+            .await;
+        info!("User operation sponsored. Hash: {:?}", user_op_hash);
+        Self::to_value(SponsorUserOperationResponse {
+            paymaster_and_data: user_op.paymaster_and_data,
+            pre_verification_gas: U256::from(user_op.pre_verification_gas),
+            verification_gas_limit: U256::from(user_op.verification_gas_limit),
+            call_gas_limit: U256::from(user_op.call_gas_limit),
+            max_fee_per_gas: U256::from(user_op.max_fee_per_gas),
+            max_priority_fee_per_gas: U256::from(user_op.max_priority_fee_per_gas),
+        })
+    }
+
+    fn to_json_rpc_error(error: SponsorshipError) -> JsonRpcError {
+        match error {
+            SponsorshipError::Denied(reason) => JsonRpcError::from(UserOperationError::new(
+                UserOperationError::REJECTED_BY_PAYMASTER,
+                reason,
+            )),
+            SponsorshipError::Failed(reason) => JsonRpcError::internal(reason),
+        }
+    }
+
+    fn get_entry_point(
+        &self,
+        request: &JsonRpcRequest,
+        index: usize,
+    ) -> Result<Address, JsonRpcError> {
+        let entry_point: Address = request.param(index)?;
+        if entry_point != CONFIG.chains[&self.chain].entrypoint_address {
+            return Err(JsonRpcError::invalid_params(format!(
+                "Entry point {:?} is not supported",
+                entry_point
+            )));
+        }
+        Ok(entry_point)
+    }
+
+    fn to_value<T>(data: T) -> Result<Value, JsonRpcError>
+    where
+        T: Serialize,
+    {
+        serde_json::to_value(data).map_err(|err| JsonRpcError::internal(err.to_string()))
+    }
+}
@@ -1,32 +1,40 @@
-use actix_web::rt::spawn;
-use bigdecimal::{BigDecimal, ToPrimitive};
-use ethers::abi::{encode, Tokenizable};
-use ethers::types::{Address, Bytes, U256};
-use ethers_signers::{LocalWallet, Signer};
+use bigdecimal::BigDecimal;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::types::{Address, Bytes, H256, U256};
 use log::info;
 use sqlx::{Pool, Postgres};
 use std::str::FromStr;
+use std::sync::Arc;
 
-use crate::bundler::bundler::Bundler;
-use crate::contracts::entrypoint_provider::EntryPointProvider;
-use crate::contracts::simple_account_factory_provider::SimpleAccountFactoryProvider;
-use crate::contracts::simple_account_provider::SimpleAccountProvider;
-use crate::contracts::usdc_provider::USDCProvider;
+use crate::constants::Constants;
+use crate::contracts::token_paymaster_provider::TokenPaymasterProvider;
+use crate::db::dao::call_target_dao::CallTargetDao;
+use crate::db::dao::idempotency_key_dao::IdempotencyKeyDao;
 use crate::db::dao::token_metadata_dao::TokenMetadataDao;
-use crate::db::dao::transaction_dao::{TransactionDao, TransactionMetadata, UserTransaction};
+use crate::db::dao::transaction_dao::{Gas, TransactionDao, TransactionMetadata, UserTransaction};
 use crate::db::dao::wallet_dao::{User, WalletDao};
 use crate::errors::ApiError;
 use crate::models::contract_interaction::user_operation::UserOperation;
 use crate::models::currency::Currency;
 use crate::models::transaction::transaction::Transaction;
 use crate::models::transaction_type::TransactionType;
+use crate::models::transfer::amount::Amount;
+use crate::models::transfer::batch_transfer_request::BatchTransfer;
+use crate::models::transfer::batch_transfer_response::BatchTransferResponse;
+use crate::models::transfer::contract_call_request::ContractCallRequest;
 use crate::models::transfer::status::Status;
 use crate::models::transfer::transaction_response::TransactionResponse;
+use crate::models::transfer::transfer_request::TransferRequest;
 use crate::models::transfer::transfer_response::TransferResponse;
-use crate::provider::helpers::{generate_txn_id, get_explorer_url};
-use crate::provider::listeners::user_op_event_listener;
-use crate::provider::paymaster_provider::PaymasterProvider;
-use crate::provider::verifying_paymaster_helper::get_verifying_paymaster_user_operation_payload;
+use crate::provider::chain_registry::{ChainContext, ChainRegistry};
+use crate::provider::fee_oracle::FeeOracle;
+use crate::provider::helpers::{encode_function_call, generate_txn_id, to_u256};
+use crate::provider::key_manager::KeyManager;
+use crate::provider::paymaster_sponsor::{GasPayment, PaymasterSponsor, SponsorshipError};
+use crate::provider::rate_limiter::RateLimiter;
+use crate::provider::sponsorship_policy::{SponsorshipPolicy, SponsorshipScope};
+use crate::signer::local_signer::LocalSigner;
+use crate::signer::Signer;
 use crate::CONFIG;
 
 #[derive(Clone)]
@@ -34,148 +42,626 @@ pub struct TransferService {
     pub wallet_dao: WalletDao,
     pub transaction_dao: TransactionDao,
     pub token_metadata_dao: TokenMetadataDao,
-    pub usdc_provider: USDCProvider,
-    pub entrypoint_provider: EntryPointProvider,
-    pub simple_account_provider: SimpleAccountProvider,
-    pub simple_account_factory_provider: SimpleAccountFactoryProvider,
-    pub verifying_paymaster_provider: PaymasterProvider,
-    pub verifying_paymaster_wallet: LocalWallet,
-    pub scw_owner_wallet: LocalWallet,
-    pub bundler: Bundler,
+    pub call_target_dao: CallTargetDao,
+    pub chain_registry: ChainRegistry,
+    pub verifying_paymaster_signer: Arc<dyn Signer>,
+    pub scw_owner_signer: Arc<dyn Signer>,
+    pub key_manager: KeyManager,
+    pub paymaster_sponsor: PaymasterSponsor,
+    pub rate_limiter: RateLimiter,
+    pub idempotency_key_dao: IdempotencyKeyDao,
 }
 
 impl TransferService {
+    // A retry with the same key and request gets the original response instead of a second
+    // transfer, and one with a different request is turned away
+    pub async fn transfer_funds_once(
+        &self,
+        idempotency_key: String,
+        request: TransferRequest,
+        usr: &str,
+    ) -> Result<TransferResponse, ApiError> {
+        // an invalid request is turned away before it claims the key
+        let amount = request.get_amount()?;
+        let request_hash = request.get_request_hash();
+        let reserved = self
+            .idempotency_key_dao
+            .reserve(
+                usr.to_string(),
+                idempotency_key.clone(),
+                request_hash.clone(),
+            )
+            .await;
+        match reserved {
+            Ok(None) => {}
+            Ok(Some(original)) => {
+                if original.request_hash != request_hash {
+                    return Err(ApiError::Conflict(
+                        "Idempotency-Key was used for a different request".to_string(),
+                    ));
+                }
+                return match original.transaction_id {
+                    Some(transaction_id) => Ok(Self::get_pending_response(transaction_id)),
+                    None => Err(ApiError::Conflict(
+                        "A request with this Idempotency-Key is in progress".to_string(),
+                    )),
+                };
+            }
+            Err(err) => return Err(ApiError::InternalServer(err)),
+        }
+        let response = self
+            .transfer_funds(
+                request.get_receiver(),
+                amount,
+                request.metadata.get_currency(),
+                request.metadata.get_chain(),
+                request.get_gas_currency(),
+                usr,
+            )
+            .await;
+        match &response {
+            Ok(response) => {
+                self.idempotency_key_dao
+                    .complete(
+                        usr.to_string(),
+                        idempotency_key,
+                        response.transaction_id.clone(),
+                    )
+                    .await
+            }
+            Err(_) => {
+                self.idempotency_key_dao
+                    .release(usr.to_string(), idempotency_key)
+                    .await
+            }
+        }
+        response
+    }
+
     pub async fn transfer_funds(
         &self,
         to: String,
-        value: String,
+        amount: Amount,
         currency: String,
+        chain: String,
+        gas_currency: Option<String>,
         usr: &str,
     ) -> Result<TransferResponse, ApiError> {
-        let user_wallet = self.wallet_dao.get_wallet(usr.to_string()).await;
-        let wallet: User;
-        match user_wallet {
-            None => {
-                return Err(ApiError::NotFound("Wallet not found".to_string()));
+        let context = self.chain_registry.get(&chain)?;
+        let value = self.get_base_units(context, &currency, &amount).await?;
+        let wallet = self.get_wallet(context, usr).await?;
+        let mut user_txn = self.get_user_transaction(
+            context,
+            &to,
+            &value,
+            &currency,
+            wallet.wallet_address.clone(),
+            None,
+        );
+        let call = self.get_call(context, to, value.clone(), currency).await;
+        if call.is_err() {
+            return Err(ApiError::BadRequest(call.err().unwrap()));
+        }
+        let mut calls = vec![call.unwrap()];
+        let scope = SponsorshipScope {
+            currencies: vec![user_txn.currency.clone()],
+            targets: vec![],
+        };
+        let gas_payment = match gas_currency {
+            None => GasPayment::Sponsored(scope),
+            Some(gas_currency) => {
+                let token_paymaster = match context.token_paymaster_providers.get(&gas_currency) {
+                    Some(token_paymaster) => token_paymaster.clone(),
+                    None => {
+                        return Err(ApiError::BadRequest(
+                            "Gas currency not supported".to_string(),
+                        ))
+                    }
+                };
+                let approval = self
+                    .get_paymaster_approval(context, &wallet, &token_paymaster)
+                    .await?;
+                match approval {
+                    Some(approval) => {
+                        calls.insert(0, approval);
+                        GasPayment::Sponsored(scope)
+                    }
+                    None => {
+                        user_txn.metadata.gas_erc20(Gas {
+                            currency: gas_currency,
+                            value: U256::zero(),
+                        });
+                        GasPayment::Token(token_paymaster)
+                    }
+                }
             }
-            Some(_) => {
-                wallet = user_wallet.unwrap();
+        };
+        let call_data = self.get_call_data(context, calls)?;
+        let quota = self
+            .rate_limiter
+            .check_transfers(usr, &context.chain, &[(user_txn.currency.clone(), value)])
+            .await?;
+        let result = self
+            .submit_user_operation(
+                context,
+                &wallet,
+                call_data,
+                vec![user_txn.clone()],
+                user_txn.transaction_id.clone(),
+                gas_payment,
+            )
+            .await;
+        if result.is_err() {
+            self.rate_limiter.refund(quota).await;
+            return Err(result.err().unwrap());
+        }
+
+        Ok(Self::get_pending_response(user_txn.transaction_id))
+    }
+
+    fn get_pending_response(transaction_id: String) -> TransferResponse {
+        TransferResponse {
+            transaction: TransactionResponse {
+                transaction_hash: String::new(),
+                status: Status::PENDING.to_string(),
+                explorer: String::new(),
+            },
+            transaction_id,
+        }
+    }
+
+    pub async fn batch_transfer_funds(
+        &self,
+        transfers: Vec<BatchTransfer>,
+        chain: String,
+        usr: &str,
+    ) -> Result<BatchTransferResponse, ApiError> {
+        if transfers.is_empty() {
+            return Err(ApiError::BadRequest("No transfers in batch".to_string()));
+        }
+        if transfers.len() > Constants::MAX_BATCH_TRANSFERS {
+            return Err(ApiError::BadRequest(format!(
+                "A batch can hold at most {} transfers",
+                Constants::MAX_BATCH_TRANSFERS
+            )));
+        }
+        let context = self.chain_registry.get(&chain)?;
+        let wallet = self.get_wallet(context, usr).await?;
+        let batch_id = generate_txn_id();
+        let mut targets = vec![];
+        let mut funcs = vec![];
+        let mut user_txns = vec![];
+        let mut values = vec![];
+        for transfer in transfers {
+            let value = self
+                .get_base_units(context, &transfer.get_currency(), &transfer.get_amount()?)
+                .await?;
+            let call = self
+                .get_call(
+                    context,
+                    transfer.get_receiver(),
+                    value.clone(),
+                    transfer.get_currency(),
+                )
+                .await;
+            if call.is_err() {
+                return Err(ApiError::BadRequest(call.err().unwrap()));
+            }
+            let (target, call_value, func) = call.unwrap();
+            // executeBatch forwards no value, so native legs cannot be part of a batch
+            if call_value != "0" {
+                return Err(ApiError::BadRequest(
+                    "Native transfers cannot be batched".to_string(),
+                ));
             }
+            targets.push(target);
+            funcs.push(func);
+            user_txns.push(self.get_user_transaction(
+                context,
+                &transfer.get_receiver(),
+                &value,
+                &transfer.get_currency(),
+                wallet.wallet_address.clone(),
+                Some(batch_id.clone()),
+            ));
+            values.push((transfer.get_currency(), value));
+        }
+        let call_data = context
+            .simple_account_provider
+            .execute_batch(targets, funcs);
+        if call_data.is_err() {
+            return Err(ApiError::InternalServer(call_data.err().unwrap()));
+        }
+        let quota = self
+            .rate_limiter
+            .check_transfers(usr, &context.chain, &values)
+            .await?;
+        let scope = SponsorshipScope {
+            currencies: user_txns
+                .iter()
+                .map(|user_txn| user_txn.currency.clone())
+                .collect(),
+            targets: vec![],
+        };
+        let result = self
+            .submit_user_operation(
+                context,
+                &wallet,
+                call_data.unwrap(),
+                user_txns.clone(),
+                batch_id.clone(),
+                GasPayment::Sponsored(scope),
+            )
+            .await;
+        if result.is_err() {
+            self.rate_limiter.refund(quota).await;
+            return Err(result.err().unwrap());
+        }
+
+        Ok(BatchTransferResponse {
+            transaction: TransactionResponse {
+                transaction_hash: String::new(),
+                status: Status::PENDING.to_string(),
+                explorer: String::new(),
+            },
+            batch_id,
+            transaction_ids: user_txns
+                .into_iter()
+                .map(|user_txn| user_txn.transaction_id)
+                .collect(),
+        })
+    }
+
+    pub async fn call_contract(
+        &self,
+        request: ContractCallRequest,
+        usr: &str,
+    ) -> Result<TransferResponse, ApiError> {
+        let context = self.chain_registry.get(&request.get_chain())?;
+        let target: Address = match request.get_target().parse() {
+            Ok(target) => target,
+            Err(_) => return Err(ApiError::BadRequest("Invalid target address".to_string())),
+        };
+        if !self
+            .call_target_dao
+            .is_allowed(context.chain.clone(), request.get_target())
+            .await
+        {
+            return Err(ApiError::BadRequest("Target not allowed".to_string()));
         }
-        let mut user_txn =
-            self.get_user_transaction(&to, &value, &currency, wallet.wallet_address.clone());
+        let value = request.get_value();
+        if U256::from_dec_str(&value).is_err() {
+            return Err(ApiError::BadRequest("Invalid value".to_string()));
+        }
+        let func = match (&request.data, &request.function) {
+            (Some(data), None) => data
+                .parse::<Bytes>()
+                .map_err(|_| "Invalid calldata".to_string()),
+            (None, Some(function)) => encode_function_call(function, &request.args),
+            _ => Err("Either data or function is required".to_string()),
+        };
+        if func.is_err() {
+            return Err(ApiError::BadRequest(func.err().unwrap()));
+        }
+        let native_currency = self.get_native_currency(context).await;
+        if native_currency.is_err() {
+            return Err(ApiError::BadRequest(native_currency.err().unwrap()));
+        }
+
+        let wallet = self.get_wallet(context, usr).await?;
+        let mut user_txn = self.get_user_transaction(
+            context,
+            &request.get_target(),
+            &value,
+            &native_currency.unwrap(),
+            wallet.wallet_address.clone(),
+            None,
+        );
+        user_txn.transaction_type(TransactionType::Call.to_string());
+        let scope = SponsorshipScope {
+            currencies: if value == "0" {
+                vec![]
+            } else {
+                vec![user_txn.currency.clone()]
+            },
+            targets: vec![target],
+        };
+        let call_data = context
+            .simple_account_provider
+            .execute(target, value.clone(), func.unwrap())
+            .unwrap();
+        let quota = self
+            .rate_limiter
+            .check_transfers(usr, &context.chain, &[(user_txn.currency.clone(), value)])
+            .await?;
+        let result = self
+            .submit_user_operation(
+                context,
+                &wallet,
+                call_data,
+                vec![user_txn.clone()],
+                user_txn.transaction_id.clone(),
+                GasPayment::Sponsored(scope),
+            )
+            .await;
+        if result.is_err() {
+            self.rate_limiter.refund(quota).await;
+            return Err(result.err().unwrap());
+        }
+
+        Ok(Self::get_pending_response(user_txn.transaction_id))
+    }
+
+    // the value sent along with a contract call is recorded in the chain's native currency
+    async fn get_native_currency(&self, context: &ChainContext) -> Result<String, String> {
+        self.token_metadata_dao
+            .get_metadata_for_chain(context.chain.clone(), None)
+            .await
+            .into_iter()
+            .find(|metadata| {
+                matches!(
+                    Currency::from_str(metadata.token_type.clone()),
+                    Some(Currency::Native)
+                )
+            })
+            .map(|metadata| metadata.symbol)
+            .ok_or(String::from("Native currency not supported"))
+    }
+
+    async fn get_wallet(&self, context: &ChainContext, usr: &str) -> Result<User, ApiError> {
+        let user_wallet = self
+            .wallet_dao
+            .get_wallet(usr.to_string(), context.chain.clone())
+            .await;
+        match user_wallet {
+            None => Err(ApiError::NotFound("Wallet not found".to_string())),
+            Some(wallet) => Ok(wallet),
+        }
+    }
+
+    // The user operation is tracked by `tracking_id`, which is either the id of its only
+    // transaction or the batch id shared by all of them.
+    async fn submit_user_operation(
+        &self,
+        context: &ChainContext,
+        wallet: &User,
+        call_data: Bytes,
+        user_txns: Vec<UserTransaction>,
+        tracking_id: String,
+        gas_payment: GasPayment,
+    ) -> Result<(), ApiError> {
+        // wallets created before per-user owner keys are still owned by the global account owner
+        let (owner_address, owner_signer): (Address, Arc<dyn Signer>) =
+            match wallet.encrypted_owner_key.clone() {
+                Some(encrypted_owner_key) => {
+                    let owner_wallet = self.key_manager.decrypt(&encrypted_owner_key);
+                    if owner_wallet.is_err() {
+                        return Err(ApiError::InternalServer(owner_wallet.err().unwrap()));
+                    }
+                    let owner_signer = LocalSigner::new(owner_wallet.unwrap());
+                    (owner_signer.address(), Arc::new(owner_signer))
+                }
+                None => (
+                    CONFIG.run_config.account_owner,
+                    self.scw_owner_signer.clone(),
+                ),
+            };
+        let wallet_address: Address = wallet.wallet_address.parse().unwrap();
+        let on_chain_nonce = context.entrypoint_provider.get_nonce(wallet_address).await;
+        if on_chain_nonce.is_err() {
+            return Err(ApiError::InternalServer(on_chain_nonce.err().unwrap()));
+        }
+        let nonce = context
+            .bundler
+            .mempool
+            .next_nonce(wallet_address, on_chain_nonce.unwrap().low_u64())
+            .await;
         let mut user_op0 = UserOperation::new();
-        user_op0.calldata(self.get_call_data(to, value, currency).await.unwrap());
-        if !wallet.deployed {
+        user_op0.calldata(call_data);
+        // only the wallet's first operation deploys it, the receipt tracker flags it once confirmed
+        if !wallet.deployed && nonce == 0 {
             user_op0.init_code(
-                self.simple_account_factory_provider.abi.address(),
-                self.simple_account_factory_provider
-                    .create_account(
-                        CONFIG.run_config.account_owner,
-                        U256::from(wallet.salt.to_u64().unwrap()),
-                    )
+                context.simple_account_factory_provider.abi.address(),
+                context
+                    .simple_account_factory_provider
+                    .create_account(owner_address, to_u256(&wallet.salt).unwrap())
                     .unwrap(),
             );
         }
 
-        let wallet_address: Address = wallet.wallet_address.parse().unwrap();
-        let valid_until: u64 = 3735928559;
-        let valid_after: u64 = 4660;
-        let data = encode(&vec![valid_until.into_token(), valid_after.into_token()]);
-        user_op0
-            .paymaster_and_data(data.clone(), wallet_address.clone(), None)
-            .nonce(
-                self.entrypoint_provider
-                    .get_nonce(wallet_address)
+        let validity_window = SponsorshipPolicy::get_validity_window();
+        let dummy_paymaster_and_data = match &gas_payment {
+            GasPayment::Sponsored(_) => {
+                self.paymaster_sponsor
+                    .get_dummy_paymaster_and_data(&context.chain, validity_window)
                     .await
-                    .unwrap()
-                    .low_u64(),
-            )
-            .sender(wallet_address.clone());
+            }
+            GasPayment::Token(token_paymaster) => Ok(token_paymaster.get_paymaster_and_data()),
+        };
+        if dummy_paymaster_and_data.is_err() {
+            return Err(Self::to_api_error(dummy_paymaster_and_data.err().unwrap()));
+        }
+        let gas_fees = FeeOracle::get_fees(&context.chain).await;
+        if gas_fees.is_err() {
+            return Err(ApiError::InternalServer(gas_fees.err().unwrap()));
+        }
+        user_op0
+            .gas_fees(gas_fees.unwrap())
+            .nonce(nonce)
+            .sender(wallet_address)
+            .paymaster_and_data(dummy_paymaster_and_data.unwrap());
 
-        user_op0.signature(Bytes::from(
-            self.verifying_paymaster_wallet
-                .sign_typed_data(&user_op0)
-                .await
-                .unwrap()
-                .to_vec(),
-        ));
+        let dummy_signature = self
+            .verifying_paymaster_signer
+            .sign_hash(H256::from(user_op0.encode_eip712().unwrap()))
+            .await;
+        if dummy_signature.is_err() {
+            return Err(ApiError::InternalServer(dummy_signature.err().unwrap()));
+        }
+        user_op0.signature(Bytes::from(dummy_signature.unwrap().to_vec()));
 
-        let singed_hash = self
-            .get_signed_hash(user_op0.clone(), valid_until, valid_after)
+        let estimation = context.gas_estimator.fill(&mut user_op0).await;
+        if estimation.is_err() {
+            return Err(ApiError::UserOperationRejected(estimation.err().unwrap()));
+        }
+        let mut reservation = None;
+        if let GasPayment::Sponsored(scope) = &gas_payment {
+            let sponsorship = self
+                .paymaster_sponsor
+                .sponsor(
+                    &context.chain,
+                    &context.verifying_paymaster_provider,
+                    &user_op0,
+                    scope,
+                    validity_window,
+                )
+                .await;
+            if sponsorship.is_err() {
+                return Err(Self::to_api_error(sponsorship.err().unwrap()));
+            }
+            let (paymaster_and_data, sponsorship_reservation) = sponsorship.unwrap();
+            user_op0.paymaster_and_data(paymaster_and_data);
+            reservation = Some(sponsorship_reservation);
+        }
+
+        let result = self
+            .queue_user_operation(context, user_op0, owner_signer, user_txns, tracking_id)
             .await;
-        user_op0.paymaster_and_data(
-            data,
-            CONFIG.get_chain().verifying_paymaster_address,
-            Some(singed_hash),
-        );
+        if let Some(reservation) = reservation {
+            let sponsorship_policy = &self.paymaster_sponsor.sponsorship_policy;
+            match &result {
+                Ok(user_op_hash) => sponsorship_policy.record(reservation, *user_op_hash).await,
+                Err(_) => sponsorship_policy.release(reservation).await,
+            }
+        }
+        result.map(|_| ())
+    }
 
+    // signs the final operation and hands it to the bundler along with its user transactions
+    async fn queue_user_operation(
+        &self,
+        context: &ChainContext,
+        mut user_op0: UserOperation,
+        owner_signer: Arc<dyn Signer>,
+        user_txns: Vec<UserTransaction>,
+        tracking_id: String,
+    ) -> Result<H256, ApiError> {
         let user_op_hash = user_op0.hash(
-            CONFIG.get_chain().entrypoint_address,
-            CONFIG.get_chain().chain_id,
-        );
-        let signature = Bytes::from(
-            self.scw_owner_wallet
-                .sign_message(user_op_hash.clone())
-                .await
-                .unwrap()
-                .to_vec(),
+            CONFIG.chains[&context.chain].entrypoint_address,
+            CONFIG.chains[&context.chain].chain_id,
         );
-
-        user_op0.signature(signature);
-        let result = self
+        let signature = owner_signer.sign_message(&user_op_hash).await;
+        if signature.is_err() {
+            return Err(ApiError::InternalServer(signature.err().unwrap()));
+        }
+        user_op0.signature(Bytes::from(signature.unwrap().to_vec()));
+        for user_txn in user_txns {
+            self.transaction_dao.create_user_transaction(user_txn).await;
+        }
+        let result = context
             .bundler
-            .submit(user_op0, CONFIG.run_config.account_owner)
+            .add(
+                user_op0.clone(),
+                H256::from(user_op_hash),
+                Some(tracking_id.clone()),
+            )
             .await;
         if result.is_err() {
-            user_txn.status(Status::FAILED.to_string());
-            self.transaction_dao.create_user_transaction(user_txn).await;
-            return Err(ApiError::BadRequest(result.err().unwrap()));
+            self.transaction_dao
+                .update_user_transactions_status(vec![tracking_id], Status::FAILED.to_string())
+                .await;
+            return Err(ApiError::UserOperationRejected(result.err().unwrap()));
         }
 
-        let txn_hash = result.unwrap();
-        info!("Transaction sent successfully. Hash: {:?}", txn_hash);
-        user_txn
-            .metadata
-            .transaction_hash(txn_hash.clone().to_lowercase());
-        self.transaction_dao
-            .create_user_transaction(user_txn.clone())
+        info!(
+            "User operation queued. Hash: {:?}",
+            H256::from(user_op_hash)
+        );
+        Ok(H256::from(user_op_hash))
+    }
+
+    // The TokenPaymaster takes its charge during validation, so the wallet has to approve it
+    // beforehand. The approval is unlimited and goes out with a sponsored operation.
+    async fn get_paymaster_approval(
+        &self,
+        context: &ChainContext,
+        wallet: &User,
+        token_paymaster: &TokenPaymasterProvider,
+    ) -> Result<Option<(Address, String, Bytes)>, ApiError> {
+        let token = token_paymaster.get_token().await;
+        if token.is_err() {
+            return Err(ApiError::InternalServer(token.err().unwrap()));
+        }
+        let token = token.unwrap();
+        let erc20_provider = context.erc20_provider_registry.get(&format!("{:?}", token));
+        if erc20_provider.is_err() {
+            return Err(ApiError::InternalServer(erc20_provider.err().unwrap()));
+        }
+        let erc20_provider = erc20_provider.unwrap();
+        let allowance = erc20_provider
+            .allowance(
+                wallet.wallet_address.parse().unwrap(),
+                token_paymaster.abi.address(),
+            )
             .await;
-        if !wallet.deployed {
-            self.wallet_dao
-                .update_wallet_deployed(usr.to_string())
-                .await;
+        if allowance.is_err() {
+            return Err(ApiError::InternalServer(allowance.err().unwrap()));
         }
+        if allowance.unwrap() >= U256::MAX / 2 {
+            return Ok(None);
+        }
+        let approve = erc20_provider.approve(token_paymaster.abi.address(), U256::MAX);
+        if approve.is_err() {
+            return Err(ApiError::InternalServer(approve.err().unwrap()));
+        }
+        Ok(Some((token, 0.to_string(), approve.unwrap())))
+    }
 
-        spawn(user_op_event_listener(
-            self.transaction_dao.clone(),
-            self.entrypoint_provider.clone(),
-            user_op_hash,
-            user_txn.transaction_id.clone(),
-        ));
-
-        Ok(TransferResponse {
-            transaction: TransactionResponse {
-                transaction_hash: txn_hash.clone(),
-                status: Status::PENDING.to_string(),
-                explorer: get_explorer_url(&txn_hash),
-            },
-            transaction_id: user_txn.transaction_id,
-        })
+    fn get_call_data(
+        &self,
+        context: &ChainContext,
+        calls: Vec<(Address, String, Bytes)>,
+    ) -> Result<Bytes, ApiError> {
+        if calls.len() == 1 {
+            let (target, value, func) = calls[0].clone();
+            return Ok(context
+                .simple_account_provider
+                .execute(target, value, func)
+                .unwrap());
+        }
+        // executeBatch forwards no value, so native transfers cannot go along with an approval
+        if calls.iter().any(|(_, value, _)| value != "0") {
+            return Err(ApiError::BadRequest(
+                "Approve the gas currency with a token transfer first".to_string(),
+            ));
+        }
+        let (targets, funcs) = calls
+            .into_iter()
+            .map(|(target, _, func)| (target, func))
+            .unzip();
+        let call_data = context
+            .simple_account_provider
+            .execute_batch(targets, funcs);
+        if call_data.is_err() {
+            return Err(ApiError::InternalServer(call_data.err().unwrap()));
+        }
+        Ok(call_data.unwrap())
     }
 
-    fn get_transaction_metadata(&self) -> TransactionMetadata {
+    fn get_transaction_metadata(&self, context: &ChainContext) -> TransactionMetadata {
         let mut txn_metadata = TransactionMetadata::new();
-        txn_metadata.chain(CONFIG.run_config.current_chain.clone());
+        txn_metadata.chain(context.chain.clone());
         txn_metadata
     }
 
     fn get_user_transaction(
         &self,
+        context: &ChainContext,
         to: &String,
         value: &String,
         currency: &String,
         wallet_address: String,
+        batch_id: Option<String>,
     ) -> UserTransaction {
         let mut user_txn = UserTransaction::new();
         user_txn
@@ -187,59 +673,67 @@ impl TransferService {
             .currency(currency.clone())
             .transaction_type(TransactionType::Debit.to_string())
             .status(Status::PENDING.to_string())
-            .metadata(self.get_transaction_metadata());
+            .metadata(self.get_transaction_metadata(context))
+            .batch_id(batch_id);
         user_txn
     }
 
-    async fn get_signed_hash(
+    fn to_api_error(error: SponsorshipError) -> ApiError {
+        match error {
+            SponsorshipError::Denied(reason) => ApiError::SponsorshipDenied(reason),
+            SponsorshipError::Failed(reason) => ApiError::InternalServer(reason),
+        }
+    }
+
+    // the transfer's value in base units of the currency, checked against its exponent
+    async fn get_base_units(
         &self,
-        user_op0: UserOperation,
-        valid_until: u64,
-        valid_after: u64,
-    ) -> Vec<u8> {
-        let hash = self
-            .verifying_paymaster_provider
-            .get_hash(
-                get_verifying_paymaster_user_operation_payload(user_op0),
-                valid_until,
-                valid_after,
-            )
-            .await
-            .unwrap();
-        self.verifying_paymaster_wallet
-            .sign_message(hash)
-            .await
-            .unwrap()
-            .to_vec()
+        context: &ChainContext,
+        currency: &str,
+        amount: &Amount,
+    ) -> Result<String, ApiError> {
+        let metadata = self
+            .token_metadata_dao
+            .get_metadata_for_chain(context.chain.clone(), Some(currency.to_string()))
+            .await;
+        if metadata.is_empty() {
+            return Err(ApiError::BadRequest("Currency not found".to_string()));
+        }
+        Ok(amount.to_base_units(metadata[0].exponent)?.to_string())
     }
 
-    async fn get_call_data(
+    // target, value and data of the call the smart account makes for a transfer
+    async fn get_call(
         &self,
+        context: &ChainContext,
         to: String,
         value: String,
         currency: String,
-    ) -> Result<Bytes, String> {
-        match Currency::from_str(
-            self.token_metadata_dao
-                .get_metadata_for_chain(CONFIG.run_config.current_chain.clone(), Some(currency))
-                .await[0]
-                .token_type
-                .clone(),
-        ) {
-            Some(Currency::Erc20) => Ok(self
-                .simple_account_provider
-                .execute(
-                    CONFIG.get_chain().usdc_address,
+    ) -> Result<(Address, String, Bytes), String> {
+        let metadata = self
+            .token_metadata_dao
+            .get_metadata_for_chain(context.chain.clone(), Some(currency))
+            .await;
+        if metadata.is_empty() {
+            return Err("Currency not found".to_string());
+        }
+        let metadata = metadata[0].clone();
+        let to: Address = match to.parse() {
+            Ok(to) => to,
+            Err(_) => return Err("Invalid receiver address".to_string()),
+        };
+        match Currency::from_str(metadata.token_type.clone()) {
+            Some(Currency::Erc20) => {
+                let erc20_provider = context
+                    .erc20_provider_registry
+                    .get(&metadata.contract_address)?;
+                Ok((
+                    erc20_provider.abi.address(),
                     0.to_string(),
-                    self.usdc_provider
-                        .transfer(to.parse().unwrap(), value)
-                        .unwrap(),
-                )
-                .unwrap()),
-            Some(Currency::Native) => Ok(self
-                .simple_account_provider
-                .execute(to.parse().unwrap(), value, Bytes::from(vec![]))
-                .unwrap()),
+                    erc20_provider.transfer(to, value)?,
+                ))
+            }
+            Some(Currency::Native) => Ok((to, value, Bytes::from(vec![]))),
             None => Err("Currency not found".to_string()),
         }
     }
@@ -249,11 +743,11 @@ impl TransferService {
         txn_id: String,
         user_id: String,
     ) -> Result<Transaction, ApiError> {
-        let user_wallet_address =
-            WalletDao::get_user_wallet_address(db_pool, user_id.to_string()).await;
+        let user_wallet_addresses =
+            WalletDao::get_user_wallet_addresses(db_pool, user_id.to_string()).await;
 
         let transaction_and_exponent =
-            TransactionDao::get_transaction_by_id(db_pool, txn_id, user_wallet_address).await;
+            TransactionDao::get_transaction_by_id(db_pool, txn_id, user_wallet_addresses).await;
 
         Ok(Transaction::from(transaction_and_exponent))
     }
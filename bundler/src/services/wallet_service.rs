@@ -1,92 +1,104 @@
-use bigdecimal::{BigDecimal, Zero};
-use ethers::prelude::U256;
-use std::sync::Arc;
-use std::time::SystemTime;
+use bigdecimal::BigDecimal;
+use std::str::FromStr;
 
-use ethers::providers::{Http, Provider};
 use ethers::types::Address;
+use ethers_signers::Signer;
 use log::{info, warn};
 
-use crate::contracts::simple_account_factory_provider::SimpleAccountFactory;
+use crate::constants::Constants;
 use crate::contracts::simple_account_provider::SimpleAccountProvider;
 use crate::db::dao::transaction_dao::TransactionDao;
-use crate::db::dao::wallet_dao::WalletDao;
+use crate::db::dao::wallet_dao::{NewWallet, WalletDao};
 use crate::errors::ApiError;
 use crate::models::transaction::transaction::Transaction;
 use crate::models::wallet::address_response::AddressResponse;
-use crate::provider::helpers::{contract_exists_at, get_hash};
+use crate::provider::chain_registry::{ChainContext, ChainRegistry};
+use crate::provider::helpers::{contract_exists_at, get_salt};
+use crate::provider::key_manager::KeyManager;
 use crate::CONFIG;
 
 #[derive(Clone)]
 pub struct WalletService {
     pub wallet_dao: WalletDao,
     pub transaction_dao: TransactionDao,
-    pub simple_account_factory_provider: SimpleAccountFactory<Provider<Http>>,
-    pub client: Arc<Provider<Http>>,
+    pub chain_registry: ChainRegistry,
+    pub key_manager: KeyManager,
 }
 
 impl WalletService {
-    pub async fn get_wallet_address(&self, usr: &str) -> Result<AddressResponse, ApiError> {
-        let result: Wallet;
-        let address = self.wallet_dao.get_wallet_address(usr.to_string()).await;
-        if address.is_empty() {
-            result = self.get_address(usr).await;
-            info!("salt -> {}", result.salt);
-            self.wallet_dao
-                .create_wallet(
-                    usr.to_string(),
-                    format!("{:?}", result.address),
-                    result.salt,
-                    false,
-                )
-                .await;
-        } else {
-            result = Wallet {
+    pub async fn get_wallet_address(
+        &self,
+        usr: &str,
+        chain: &str,
+    ) -> Result<AddressResponse, ApiError> {
+        let context = self.chain_registry.get(chain)?;
+        let address = self
+            .wallet_dao
+            .get_wallet_address(usr.to_string(), context.chain.clone())
+            .await;
+        if !address.is_empty() {
+            return Ok(AddressResponse {
                 address: address.parse().unwrap(),
-                salt: BigDecimal::zero(),
-            }
+            });
+        }
+        let owner = self.key_manager.generate();
+        if owner.is_err() {
+            return Err(ApiError::InternalServer(owner.err().unwrap()));
         }
+        let (owner_wallet, encrypted_owner_key) = owner.unwrap();
+        let result = self.get_address(context, usr, owner_wallet.address()).await;
+        info!("salt -> {}", result.salt);
+        let address = self
+            .wallet_dao
+            .create_wallet(NewWallet {
+                user_id: usr.to_string(),
+                wallet_address: format!("{:?}", result.address),
+                salt: result.salt,
+                deployed: false,
+                owner_address: format!("{:?}", owner_wallet.address()),
+                encrypted_owner_key,
+                salt_scheme_version: Constants::SALT_SCHEME_VERSION,
+                chain: context.chain.clone(),
+            })
+            .await
+            .map_err(ApiError::InternalServer)?;
 
         Ok(AddressResponse {
-            address: result.address,
+            address: address.parse().unwrap(),
         })
     }
 
-    async fn get_address(&self, usr: &str) -> Wallet {
+    async fn get_address(&self, context: &ChainContext, usr: &str, owner: Address) -> Wallet {
         let mut result;
-        let mut suffix = "".to_string();
+        let mut attempt = 0;
         let mut salt;
         loop {
-            let user = usr.to_string().clone() + suffix.as_str();
-            salt = get_hash(user);
-            result = self
+            salt = get_salt(usr, attempt);
+            result = context
                 .simple_account_factory_provider
-                .get_address(CONFIG.run_config.account_owner, U256::from(salt))
+                .abi
+                .get_address(owner, salt)
                 .await
                 .unwrap();
-            if contract_exists_at(format!("{:?}", result)).await {
+            if contract_exists_at(&context.chain, format!("{:?}", result)).await {
                 info!("contract exists at {:?}", result);
-                if self.is_deployed_by_us(result).await {
+                if self.is_deployed_by_us(context, result).await {
                     break;
                 }
             } else {
                 break;
             }
-            suffix = SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos()
-                .to_string();
+            attempt += 1;
         }
         Wallet {
             address: result,
-            salt: BigDecimal::from(salt),
+            salt: BigDecimal::from_str(&salt.to_string()).unwrap(),
         }
     }
 
-    async fn is_deployed_by_us(&self, contract_address: Address) -> bool {
+    async fn is_deployed_by_us(&self, context: &ChainContext, contract_address: Address) -> bool {
         let simple_account_provider =
-            SimpleAccountProvider::init_abi(self.client.clone(), contract_address);
+            SimpleAccountProvider::init_abi(context.client.clone(), contract_address);
         let account_deployed_by = simple_account_provider.deployed_by().call().await;
         match account_deployed_by {
             Ok(account_deployed_by) => {
@@ -104,10 +116,12 @@ impl WalletService {
         page_size: i64,
         id: Option<i32>,
         user_id: &String,
-    ) -> Vec<Transaction> {
+        chain: &str,
+    ) -> Result<Vec<Transaction>, ApiError> {
+        let context = self.chain_registry.get(chain)?;
         let user_wallet_address = self
             .wallet_dao
-            .get_wallet_address(user_id.to_string())
+            .get_wallet_address(user_id.to_string(), context.chain.clone())
             .await;
 
         let row_id = id.unwrap_or(i32::MAX);
@@ -122,7 +136,7 @@ impl WalletService {
             transactions.push(Transaction::from(transaction_and_exponent))
         }
 
-        transactions
+        Ok(transactions)
     }
 }
 
@@ -0,0 +1,35 @@
+use async_trait::async_trait;
+use ethers::types::{Address, Signature, H256};
+use ethers_signers::LocalWallet;
+
+use crate::signer::local_signer::LocalSigner;
+use crate::signer::Signer;
+
+// An encrypted JSON keystore, decrypted once at startup with a password from env
+#[derive(Clone, Debug)]
+pub struct KeystoreSigner {
+    pub signer: LocalSigner,
+}
+
+impl KeystoreSigner {
+    pub fn decrypt(path: &str, password_env: &str) -> KeystoreSigner {
+        let password =
+            std::env::var(password_env).unwrap_or_else(|_| panic!("{} must be set", password_env));
+        let wallet = LocalWallet::decrypt_keystore(path, password)
+            .unwrap_or_else(|err| panic!("Failed to decrypt keystore {}: {:?}", path, err));
+        KeystoreSigner {
+            signer: LocalSigner::new(wallet),
+        }
+    }
+}
+
+#[async_trait]
+impl Signer for KeystoreSigner {
+    fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    async fn sign_hash(&self, hash: H256) -> Result<Signature, String> {
+        self.signer.sign_hash(hash).await
+    }
+}
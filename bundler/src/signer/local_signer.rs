@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use ethers::types::{Address, Signature, H256};
+use ethers_signers::{LocalWallet, Signer as _};
+use log::error;
+
+use crate::signer::Signer;
+
+#[derive(Clone, Debug)]
+pub struct LocalSigner {
+    pub wallet: LocalWallet,
+}
+
+impl LocalSigner {
+    pub fn new(wallet: LocalWallet) -> LocalSigner {
+        LocalSigner { wallet }
+    }
+
+    pub fn from_env(private_key_env: &str) -> LocalSigner {
+        let wallet = std::env::var(private_key_env)
+            .unwrap_or_else(|_| panic!("{} must be set", private_key_env))
+            .parse::<LocalWallet>()
+            .unwrap();
+        LocalSigner { wallet }
+    }
+}
+
+#[async_trait]
+impl Signer for LocalSigner {
+    fn address(&self) -> Address {
+        self.wallet.address()
+    }
+
+    async fn sign_hash(&self, hash: H256) -> Result<Signature, String> {
+        self.wallet.sign_hash(hash).map_err(|err| {
+            error!("Local signer failed: {:?}", err);
+            String::from("failed to sign")
+        })
+    }
+}
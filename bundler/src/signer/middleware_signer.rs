@@ -0,0 +1,93 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use derive_more::Display;
+use ethers::middleware::{NonceManagerMiddleware, SignerMiddleware};
+use ethers::providers::{Http, Provider};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::types::{Address, Signature, H256};
+use ethers_signers::to_eip155_v;
+
+use crate::signer::Signer;
+
+#[derive(Debug, Display)]
+#[display(fmt = "{}", _0)]
+pub struct MiddlewareSignerError(String);
+
+impl std::error::Error for MiddlewareSignerError {}
+
+// The relayer of a chain. The bundler, deposit monitor and admin calls share one client, so
+// nonces are handed out locally instead of read back from the chain for every send.
+pub type RelayerClient =
+    NonceManagerMiddleware<SignerMiddleware<Arc<Provider<Http>>, MiddlewareSigner>>;
+
+// Lets any `Signer` send relayer transactions through ethers' `SignerMiddleware`
+#[derive(Clone, Debug)]
+pub struct MiddlewareSigner {
+    pub signer: Arc<dyn Signer>,
+    pub chain_id: u64,
+}
+
+impl MiddlewareSigner {
+    pub fn new(signer: Arc<dyn Signer>, chain_id: u64) -> MiddlewareSigner {
+        MiddlewareSigner { signer, chain_id }
+    }
+}
+
+#[async_trait]
+impl ethers_signers::Signer for MiddlewareSigner {
+    type Error = MiddlewareSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        self.signer
+            .sign_message(message.as_ref())
+            .await
+            .map_err(MiddlewareSignerError)
+    }
+
+    async fn sign_transaction(&self, txn: &TypedTransaction) -> Result<Signature, Self::Error> {
+        let chain_id = txn
+            .chain_id()
+            .map(|id| id.as_u64())
+            .unwrap_or(self.chain_id);
+        let mut txn = txn.clone();
+        txn.set_chain_id(chain_id);
+        let mut signature = self
+            .signer
+            .sign_hash(txn.sighash())
+            .await
+            .map_err(MiddlewareSignerError)?;
+        signature.v = to_eip155_v(signature.v as u8 - 27, chain_id);
+        Ok(signature)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        let hash = payload
+            .encode_eip712()
+            .map_err(|err| MiddlewareSignerError(err.to_string()))?;
+        self.signer
+            .sign_hash(H256::from(hash))
+            .await
+            .map_err(MiddlewareSignerError)
+    }
+
+    fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
@@ -0,0 +1,7 @@
+pub mod keystore_signer;
+pub mod local_signer;
+pub mod middleware_signer;
+pub mod remote_signer;
+mod traits;
+
+pub use traits::{init_signer, Signer};
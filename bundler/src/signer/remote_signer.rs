@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use ethers::types::{Address, Signature, H256};
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::signer::Signer;
+
+// Keys held by a separate signing process, which signs raw hashes over HTTP
+#[derive(Clone, Debug)]
+pub struct RemoteSigner {
+    pub url: String,
+    pub address: Address,
+    client: reqwest::Client,
+}
+
+#[derive(Serialize)]
+struct SignRequest {
+    address: Address,
+    hash: H256,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    signature: String,
+}
+
+impl RemoteSigner {
+    pub fn new(url: &str, address: Address, timeout_ms: u64) -> RemoteSigner {
+        RemoteSigner {
+            url: url.to_string(),
+            address,
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_millis(timeout_ms))
+                .build()
+                .unwrap(),
+        }
+    }
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_hash(&self, hash: H256) -> Result<Signature, String> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&SignRequest {
+                address: self.address,
+                hash,
+            })
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        let response = match response {
+            Ok(response) => response,
+            Err(err) if err.is_timeout() => {
+                error!("Remote signer request timed out: {:?}", err);
+                return Err(String::from("remote signer timed out"));
+            }
+            Err(err) => {
+                error!("Remote signer request failed: {:?}", err);
+                return Err(String::from("remote signer unavailable"));
+            }
+        };
+        let body = response.json::<SignResponse>().await;
+        if body.is_err() {
+            error!("Remote signer response invalid: {:?}", body.err().unwrap());
+            return Err(String::from("invalid remote signer response"));
+        }
+        let mut signature = match body.unwrap().signature.parse::<Signature>() {
+            Ok(signature) => signature,
+            Err(err) => {
+                error!("Remote signer signature invalid: {:?}", err);
+                return Err(String::from("invalid remote signature"));
+            }
+        };
+        if signature.v < 27 {
+            signature.v += 27;
+        }
+        if signature.recover(hash).ok() != Some(self.address) {
+            return Err(String::from(
+                "remote signature does not match signer address",
+            ));
+        }
+        Ok(signature)
+    }
+}
@@ -0,0 +1,37 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ethers::types::{Address, Signature, H256};
+use ethers::utils::hash_message;
+
+use crate::models::config::settings::SignerConfig;
+use crate::signer::keystore_signer::KeystoreSigner;
+use crate::signer::local_signer::LocalSigner;
+use crate::signer::remote_signer::RemoteSigner;
+
+#[async_trait]
+pub trait Signer: Debug + Send + Sync {
+    fn address(&self) -> Address;
+
+    // signatures carry a v of 27 or 28
+    async fn sign_hash(&self, hash: H256) -> Result<Signature, String>;
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature, String> {
+        self.sign_hash(hash_message(message)).await
+    }
+}
+
+pub fn init_signer(config: &SignerConfig) -> Arc<dyn Signer> {
+    match config {
+        SignerConfig::Local { private_key_env } => Arc::new(LocalSigner::from_env(private_key_env)),
+        SignerConfig::Keystore { path, password_env } => {
+            Arc::new(KeystoreSigner::decrypt(path, password_env))
+        }
+        SignerConfig::Remote {
+            url,
+            address,
+            timeout_ms,
+        } => Arc::new(RemoteSigner::new(url, *address, *timeout_ms)),
+    }
+}